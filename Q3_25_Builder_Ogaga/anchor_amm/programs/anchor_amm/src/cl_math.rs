@@ -0,0 +1,333 @@
+// Concentrated liquidity math. Prices are tracked as sqrt(y / x) in Q64.64
+// fixed point and liquidity is `L = sqrt(x * y)` within a tick range, with
+// tick `i` at price `1.0001^i`. Everything here is pure so it can be tested
+// without a runtime.
+
+use amm_quote::{FEE_DENOMINATOR, U256};
+
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+// floor(2^128 / sqrt(1.0001)^(2^i)) for each bit `i` of a tick's magnitude.
+const TICK_RATIOS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e2139,
+    0xfff2e50f5f656932ef12357cf3c7fdcb,
+    0xffe5caca7e10e4e61c3624eaa0941ccf,
+    0xffcb9843d60f6159c9db58835c926643,
+    0xff973b41fa98c081472e6896dfb254bf,
+    0xff2ea16466c96a3843ec78b326b52860,
+    0xfe5dee046a99a2a811c461f1969c3052,
+    0xfcbe86c7900a88aedcffc83b479aa3a3,
+    0xf987a7253ac413176f2b074cf7815e53,
+    0xf3392b0822b70005940c7a398e4b70f2,
+    0xe7159475a2c29b7443b29c7fa6e889d8,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e4,
+    0x70d869a156d2a1b890bb3df62baf32f6,
+    0x31be135f97d08fd981231505542fcfa5,
+    0x9aa508b5b7a84e1c677de54f3e99bc8,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe97,
+];
+
+/// `sqrt(1.0001^tick)` as Q64.64, rounded up.
+pub fn sqrt_price_at_tick(tick: i32) -> Option<u128> {
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK as u32 {
+        return None;
+    }
+
+    let mut ratio = U256::one() << 128;
+    for (bit, tick_ratio) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*tick_ratio)) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    let round_up = !(ratio & U256::from(u64::MAX)).is_zero();
+    let sqrt_price = (ratio >> 64) + if round_up { U256::one() } else { U256::zero() };
+    u128::try_from(sqrt_price).ok()
+}
+
+/// The greatest tick whose sqrt price is at or below `sqrt_price`.
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Option<i32> {
+    if sqrt_price < sqrt_price_at_tick(MIN_TICK)? || sqrt_price > sqrt_price_at_tick(MAX_TICK)? {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (MIN_TICK, MAX_TICK);
+    while lo < hi {
+        let mid = lo + (hi - lo + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    Some(lo)
+}
+
+/// Amount of X between two sqrt prices for liquidity `liquidity`:
+/// `L * (sqrt_b - sqrt_a) / (sqrt_a * sqrt_b)`.
+pub fn amount_x_delta(sqrt_a: u128, sqrt_b: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (sqrt_a, sqrt_b) = (sqrt_a.min(sqrt_b), sqrt_a.max(sqrt_b));
+    if sqrt_a == 0 {
+        return None;
+    }
+
+    let numerator = (U256::from(liquidity) << 64).checked_mul(U256::from(sqrt_b - sqrt_a))?;
+    let amount = div(div(numerator, U256::from(sqrt_b), round_up), U256::from(sqrt_a), round_up);
+    u64::try_from(amount).ok()
+}
+
+/// Amount of Y between two sqrt prices for liquidity `liquidity`:
+/// `L * (sqrt_b - sqrt_a)`.
+pub fn amount_y_delta(sqrt_a: u128, sqrt_b: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (sqrt_a, sqrt_b) = (sqrt_a.min(sqrt_b), sqrt_a.max(sqrt_b));
+
+    let product = U256::from(liquidity).checked_mul(U256::from(sqrt_b - sqrt_a))?;
+    let amount = div(product, U256::one() << 64, round_up);
+    u64::try_from(amount).ok()
+}
+
+/// Sqrt price after `amount` of X (price falls) or Y (price rises) is added
+/// at `sqrt_price` with in-range liquidity `liquidity`. Rounds so the pool
+/// never gives out more than it takes in.
+pub fn next_sqrt_price_from_input(sqrt_price: u128, liquidity: u128, amount: u64, x_in: bool) -> Option<u128> {
+    if liquidity == 0 {
+        return None;
+    }
+    if amount == 0 {
+        return Some(sqrt_price);
+    }
+
+    let liquidity_q64 = U256::from(liquidity) << 64;
+    let next = match x_in {
+        // L / (L / sqrt_p + amount)
+        true => {
+            let denominator = liquidity_q64 / U256::from(sqrt_price) + U256::from(amount);
+            div(liquidity_q64, denominator, true)
+        }
+        // sqrt_p + amount / L
+        false => U256::from(sqrt_price) + (U256::from(amount) << 64) / U256::from(liquidity),
+    };
+
+    u128::try_from(next).ok()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+/// Swaps as much of `amount_remaining` as fits between `sqrt_price` and
+/// `sqrt_price_target` at constant liquidity, taking `fee` basis points from
+/// the input.
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+) -> Option<SwapStep> {
+    let x_in = sqrt_price_target <= sqrt_price;
+    let fee = fee as u128;
+
+    let amount_remaining_less_fee =
+        u64::try_from(amount_remaining as u128 * (FEE_DENOMINATOR - fee) / FEE_DENOMINATOR).ok()?;
+
+    let amount_in_to_target = match x_in {
+        true => amount_x_delta(sqrt_price_target, sqrt_price, liquidity, true),
+        false => amount_y_delta(sqrt_price, sqrt_price_target, liquidity, true),
+    };
+
+    // Past u64 the target is certainly out of reach.
+    let (sqrt_price_next, reached_target) = match amount_in_to_target {
+        Some(amount_in) if amount_remaining_less_fee >= amount_in => (sqrt_price_target, true),
+        _ => (
+            next_sqrt_price_from_input(sqrt_price, liquidity, amount_remaining_less_fee, x_in)?,
+            false,
+        ),
+    };
+
+    let (amount_in, amount_out) = match x_in {
+        true => (
+            amount_x_delta(sqrt_price_next, sqrt_price, liquidity, true)?,
+            amount_y_delta(sqrt_price_next, sqrt_price, liquidity, false)?,
+        ),
+        false => (
+            amount_y_delta(sqrt_price, sqrt_price_next, liquidity, true)?,
+            amount_x_delta(sqrt_price, sqrt_price_next, liquidity, false)?,
+        ),
+    };
+
+    let fee_amount = match reached_target {
+        // Rounding up can't be allowed to charge more than was offered.
+        true => u64::try_from(div(
+            U256::from(amount_in) * U256::from(fee),
+            U256::from(FEE_DENOMINATOR - fee),
+            true,
+        ))
+        .ok()?
+        .min(amount_remaining - amount_in),
+        // Whatever didn't go into the price move is the fee.
+        false => amount_remaining.checked_sub(amount_in)?,
+    };
+
+    Some(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// Token amounts backing `liquidity` in `[tick_lower, tick_upper)` with the
+/// pool at `sqrt_price`. Round up when adding liquidity, down when removing.
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<(u64, u64)> {
+    let sqrt_lower = sqrt_price_at_tick(tick_lower)?;
+    let sqrt_upper = sqrt_price_at_tick(tick_upper)?;
+
+    if sqrt_price <= sqrt_lower {
+        Some((amount_x_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?, 0))
+    } else if sqrt_price >= sqrt_upper {
+        Some((0, amount_y_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?))
+    } else {
+        Some((
+            amount_x_delta(sqrt_price, sqrt_upper, liquidity, round_up)?,
+            amount_y_delta(sqrt_lower, sqrt_price, liquidity, round_up)?,
+        ))
+    }
+}
+
+/// Fees owed for `liquidity` over a fee growth (Q64.64 per unit of
+/// liquidity) of `fee_growth_delta`.
+pub fn fees_owed(liquidity: u128, fee_growth_delta: u128) -> Option<u64> {
+    let owed = (U256::from(liquidity) * U256::from(fee_growth_delta)) >> 64;
+    u64::try_from(owed).ok()
+}
+
+/// Growth in fees per unit of liquidity (Q64.64) from `fee_amount` paid to
+/// `liquidity`.
+pub fn fee_growth(fee_amount: u64, liquidity: u128) -> u128 {
+    if liquidity == 0 {
+        return 0;
+    }
+    ((fee_amount as u128) << 64) / liquidity
+}
+
+fn div(numerator: U256, denominator: U256, round_up: bool) -> U256 {
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    if round_up && !remainder.is_zero() {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    #[test]
+    fn tick_zero_is_price_one() {
+        assert_eq!(sqrt_price_at_tick(0), Some(Q64));
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_tracks_powers_of_1_0001() {
+        // sqrt(1.0001^100) = 1.0050124...
+        let sqrt_price = sqrt_price_at_tick(100).unwrap();
+        let expected = (1.0001f64.powi(50) * Q64 as f64) as u128;
+        assert!(sqrt_price.abs_diff(expected) < Q64 / 1_000_000_000);
+
+        let sqrt_price = sqrt_price_at_tick(-100).unwrap();
+        let expected = (1.0001f64.powi(-50) * Q64 as f64) as u128;
+        assert!(sqrt_price.abs_diff(expected) < Q64 / 1_000_000_000);
+
+        assert!(sqrt_price_at_tick(MAX_TICK).is_some());
+        assert!(sqrt_price_at_tick(MIN_TICK).is_some());
+        assert_eq!(sqrt_price_at_tick(MAX_TICK + 1), None);
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_inverts_sqrt_price_at_tick() {
+        for tick in [MIN_TICK, -200_000, -60, -1, 0, 1, 59, 60, 123_456, MAX_TICK] {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price), Some(tick));
+            if tick < MAX_TICK {
+                assert_eq!(tick_at_sqrt_price(sqrt_price + 1), Some(tick));
+            }
+        }
+    }
+
+    #[test]
+    fn in_range_liquidity_needs_both_tokens() {
+        let (x, y) = amounts_for_liquidity(Q64, -60, 60, 1_000_000_000, true).unwrap();
+        assert!(x > 0 && y > 0);
+        assert_eq!(x, y);
+
+        let (x, y) = amounts_for_liquidity(sqrt_price_at_tick(-120).unwrap(), -60, 60, 1_000_000_000, true).unwrap();
+        assert!(x > 0 && y == 0);
+
+        let (x, y) = amounts_for_liquidity(sqrt_price_at_tick(120).unwrap(), -60, 60, 1_000_000_000, true).unwrap();
+        assert!(x == 0 && y > 0);
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target() {
+        let target = sqrt_price_at_tick(-60).unwrap();
+        let step = compute_swap_step(Q64, target, 1_000_000_000_000, u64::MAX / 2, 30).unwrap();
+
+        assert_eq!(step.sqrt_price_next, target);
+        assert_eq!(step.amount_in, amount_x_delta(target, Q64, 1_000_000_000_000, true).unwrap());
+        assert!(step.fee_amount > 0);
+    }
+
+    #[test]
+    fn swap_step_uses_up_small_inputs() {
+        let target = sqrt_price_at_tick(600).unwrap();
+        let step = compute_swap_step(Q64, target, 1_000_000_000_000, 1_000_000, 30).unwrap();
+
+        assert!(step.sqrt_price_next > Q64 && step.sqrt_price_next < target);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000_000);
+        assert_eq!(step.fee_amount, 3_000);
+        // Near price 1 the output is the input less fee, less price impact.
+        assert!(step.amount_out < 997_000 && step.amount_out > 996_000);
+    }
+
+    #[test]
+    fn swap_step_with_no_liquidity_jumps_to_the_target() {
+        let target = sqrt_price_at_tick(60).unwrap();
+        let step = compute_swap_step(Q64, target, 0, 1_000, 30).unwrap();
+
+        assert_eq!(step.sqrt_price_next, target);
+        assert_eq!((step.amount_in, step.amount_out, step.fee_amount), (0, 0, 0));
+    }
+
+    #[test]
+    fn fees_round_trip_through_fee_growth() {
+        let liquidity = 5_000_000_000;
+        let growth = fee_growth(1_000_000, liquidity);
+        let owed = fees_owed(liquidity, growth).unwrap();
+        assert!((999_999..=1_000_000).contains(&owed));
+        assert_eq!(fee_growth(1_000, 0), 0);
+    }
+}
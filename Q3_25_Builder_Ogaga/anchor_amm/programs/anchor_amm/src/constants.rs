@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+// Fee tiers (in basis points) a pool can be created with. Together with the
+// mint pair, the tier picks the pool's config address.
+#[constant]
+pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+
+// LP minted on a pool's first deposit that is locked forever, and the least
+// initial liquidity (sqrt(x * y)) a pool can be seeded with.
+#[constant]
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// Bounds on the StableSwap amplification coefficient and how fast the
+// authority may move it: a ramp must last at least MIN_RAMP_DURATION seconds
+// and change the amplification by at most MAX_AMP_CHANGE times.
+#[constant]
+pub const MIN_AMP: u64 = 1;
+#[constant]
+pub const MAX_AMP: u64 = 1_000_000;
+#[constant]
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+#[constant]
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+// Tick spacing of concentrated liquidity pools, one per entry of FEE_TIERS.
+#[constant]
+pub const TICK_SPACINGS: [u16; 4] = [1, 10, 60, 200];
+
+// Highest fee (in basis points) a dynamic-fee pool may be capped at.
+#[constant]
+pub const MAX_DYNAMIC_FEE: u16 = 1_000;
+
+// Scale of a farm's reward-per-share accumulator.
+#[constant]
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
@@ -1,5 +1,4 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
 
 #[error_code]
 pub enum AmmError {
@@ -39,18 +38,46 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Pool mints must be different.")]
+    IdenticalMints,
+    #[msg("Pool mints must be sorted so that mint_x < mint_y.")]
+    UnsortedMints,
+    #[msg("Fee is not one of the supported fee tiers.")]
+    InvalidFeeTier,
+    #[msg("Swap route accounts are malformed.")]
+    InvalidRoute,
+    #[msg("Flash loan receiver cannot be this program.")]
+    InvalidFlashLoanReceiver,
+    #[msg("Flash loan was not repaid with its fee.")]
+    FlashLoanNotRepaid,
+    #[msg("Amplification coefficient is out of range.")]
+    InvalidAmp,
+    #[msg("Amplification ramp is too fast or too short.")]
+    InvalidAmpRamp,
+    #[msg("Not supported by this pool's curve.")]
+    InvalidCurve,
+    #[msg("StableSwap pool mints must have the same decimals.")]
+    DecimalsMismatch,
+    #[msg("Tick range is invalid for this pool.")]
+    InvalidTickRange,
+    #[msg("Tick array does not cover the required tick.")]
+    InvalidTickArray,
+    #[msg("Sqrt price is out of range.")]
+    InvalidSqrtPrice,
+    #[msg("Wallet is not on this pool's allow list.")]
+    NotAllowListed,
+    #[msg("Dynamic fee bounds are invalid.")]
+    InvalidDynamicFee,
+    #[msg("Limit order is invalid.")]
+    InvalidOrder,
+    #[msg("Only the owner can cancel an order before it expires.")]
+    OrderNotExpired,
+    #[msg("This pool has been sunset; only withdrawals are allowed.")]
+    PoolSunset,
+    #[msg("Pool must be sunset before it can be closed.")]
+    PoolNotSunset,
+    #[msg("Pool still has liquidity.")]
+    PoolNotEmpty,
+    #[msg("Pool can't be reconfigured while a flash loan is out.")]
+    FlashLoanActive,
 }
-
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
-        match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::state::CurveType;
+
+// Emitted from the constant-product / StableSwap pools so indexers can follow
+// reserves, volume and LP supply from transaction logs alone. Reserves and
+// supply are the values after the instruction.
+
+#[event]
+pub struct PoolInitialized {
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub fee: u16,
+    pub curve: CurveType,
+    pub amp: u64,
+    pub authority: Option<Pubkey>,
+    pub allow_list: bool,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_minted: u64, // Including any LP locked on the first deposit
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_burned: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct PoolSunset {
+    pub config: Pubkey,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub config: Pubkey,
+}
+
+#[event]
+pub struct Swapped {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool, // Whether X was sold for Y
+    pub amount_in: u64, // Fee included
+    pub amount_out: u64,
+    pub fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::{AllowListEntry, Config}};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowList<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"allow", config.key().as_ref(), wallet.as_ref()],
+        bump,
+        space = 8 + AllowListEntry::INIT_SPACE,
+    )]
+    pub entry: Account<'info, AllowListEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddToAllowList<'info> {
+    pub fn add_to_allow_list(&mut self, wallet: Pubkey, bumps: AddToAllowListBumps) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(!self.config.flash_loan_active, AmmError::FlashLoanActive);
+
+        self.entry.set_inner(AllowListEntry {
+            config: self.config.key(),
+            wallet,
+            bump: bumps.entry,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowList<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"allow", config.key().as_ref(), entry.wallet.as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, AllowListEntry>,
+}
+
+impl<'info> RemoveFromAllowList<'info> {
+    pub fn remove_from_allow_list(&mut self) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(!self.config.flash_loan_active, AmmError::FlashLoanActive);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{cl_math, error::AmmError, state::{ClPool, TickArray}};
+
+use super::token_utils::{transfer_from_cl_vault, transfer_to_vault, unwrap_sol, wrap_sol};
+
+// Tick arrays the swap may move through are passed as writable remaining
+// accounts, starting with the one holding the current tick and ordered in
+// the direction of the swap. The swap stops early if it runs off the end.
+#[derive(Accounts)]
+pub struct ClSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"cl_pool", mint_x.key().as_ref(), mint_y.key().as_ref(), pool.fee.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClSwap<'info> {
+    pub fn cl_swap(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        is_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        sqrt_price_limit: u128,
+    ) -> Result<()> {
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        // Selling X pushes sqrt(y / x) down, selling Y pushes it up.
+        let min_sqrt_price = cl_math::sqrt_price_at_tick(cl_math::MIN_TICK).ok_or(AmmError::InvalidSqrtPrice)?;
+        let max_sqrt_price = cl_math::sqrt_price_at_tick(cl_math::MAX_TICK).ok_or(AmmError::InvalidSqrtPrice)?;
+        let sqrt_price_limit = match (sqrt_price_limit, is_x) {
+            (0, true) => min_sqrt_price,
+            (0, false) => max_sqrt_price,
+            (limit, _) => limit,
+        };
+        let limit_valid = match is_x {
+            true => sqrt_price_limit < self.pool.sqrt_price && sqrt_price_limit >= min_sqrt_price,
+            false => sqrt_price_limit > self.pool.sqrt_price && sqrt_price_limit <= max_sqrt_price,
+        };
+        require!(limit_valid, AmmError::InvalidSqrtPrice);
+
+        let mut tick_arrays = Vec::with_capacity(remaining_accounts.len());
+        for account in remaining_accounts {
+            let tick_array = Account::<TickArray>::try_from(account)?;
+            require_keys_eq!(tick_array.pool, self.pool.key(), AmmError::InvalidTickArray);
+            require!(
+                tick_arrays.iter().all(|a: &Account<TickArray>| a.key() != tick_array.key()),
+                AmmError::InvalidTickArray
+            );
+            tick_arrays.push(tick_array);
+        }
+
+        let pool = &mut self.pool;
+        let tick_spacing = pool.tick_spacing;
+        let mut amount_remaining = amount_in;
+        let mut amount_out: u64 = 0;
+
+        while amount_remaining > 0 && pool.sqrt_price != sqrt_price_limit {
+            let Some((next_tick, next_tick_array)) =
+                next_initialized_tick(&tick_arrays, pool.tick_current, tick_spacing, is_x)
+            else {
+                break;
+            };
+
+            let sqrt_price_next_tick = cl_math::sqrt_price_at_tick(next_tick).ok_or(AmmError::InvalidSqrtPrice)?;
+            let sqrt_price_target = match is_x {
+                true => sqrt_price_next_tick.max(sqrt_price_limit),
+                false => sqrt_price_next_tick.min(sqrt_price_limit),
+            };
+
+            let step = cl_math::compute_swap_step(
+                pool.sqrt_price,
+                sqrt_price_target,
+                pool.liquidity,
+                amount_remaining,
+                pool.fee,
+            )
+            .ok_or(AmmError::CurveError)?;
+
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_in)
+                .and_then(|a| a.checked_sub(step.fee_amount))
+                .ok_or(AmmError::Underflow)?;
+            amount_out = amount_out.checked_add(step.amount_out).ok_or(AmmError::Overflow)?;
+
+            let growth = cl_math::fee_growth(step.fee_amount, pool.liquidity);
+            match is_x {
+                true => pool.fee_growth_global_x = pool.fee_growth_global_x.wrapping_add(growth),
+                false => pool.fee_growth_global_y = pool.fee_growth_global_y.wrapping_add(growth),
+            }
+
+            pool.sqrt_price = step.sqrt_price_next;
+
+            if step.sqrt_price_next == sqrt_price_next_tick {
+                if let Some(index) = next_tick_array {
+                    let liquidity_net = tick_arrays[index]
+                        .tick_mut(next_tick, tick_spacing)?
+                        .cross(pool.fee_growth_global_x, pool.fee_growth_global_y);
+                    let liquidity_delta = match is_x {
+                        true => liquidity_net.checked_neg().ok_or(AmmError::Overflow)?,
+                        false => liquidity_net,
+                    };
+                    pool.liquidity = pool
+                        .liquidity
+                        .checked_add_signed(liquidity_delta)
+                        .ok_or(AmmError::Overflow)?;
+                }
+                pool.tick_current = match is_x {
+                    true => next_tick - 1,
+                    false => next_tick,
+                };
+            } else {
+                pool.tick_current =
+                    cl_math::tick_at_sqrt_price(pool.sqrt_price).ok_or(AmmError::InvalidSqrtPrice)?;
+            }
+        }
+
+        for tick_array in &tick_arrays {
+            tick_array.exit(&crate::ID)?;
+        }
+
+        let amount_used = amount_in - amount_remaining;
+        require!(amount_used != 0 && amount_out != 0, AmmError::InvalidAmount);
+        require!(amount_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(is_x, amount_used)?;
+        self.withdraw_tokens(is_x, amount_out)?;
+        self.unwrap_native()
+    }
+
+    fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (mint, from, to) = match is_x {
+            true => (self.mint_x.key(), self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.mint_y.key(), self.user_y.to_account_info(), self.vault_y.to_account_info()),
+        };
+
+        wrap_sol(
+            mint,
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.user.to_account_info(),
+            from.clone(),
+            amount,
+        )?;
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            from,
+            to,
+            self.user.to_account_info(),
+            amount,
+        )
+    }
+
+    fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+            false => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+        };
+
+        transfer_from_cl_vault(
+            self.token_program.to_account_info(),
+            &self.pool,
+            self.pool.to_account_info(),
+            from,
+            to,
+            amount,
+        )
+    }
+
+    fn unwrap_native(&self) -> Result<()> {
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.user_x.to_account_info(),
+            self.user.to_account_info(),
+            self.user_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.user_y.to_account_info(),
+            self.user.to_account_info(),
+            self.user_y.amount,
+        )
+    }
+}
+
+// The next tick the swap has to stop at, walking down (selling X) or up
+// (selling Y) from `tick_current`: either an initialized tick, with the index
+// of the tick array holding it, or the last tick the given arrays cover.
+// Returns None once the walk has left the given arrays.
+fn next_initialized_tick(
+    tick_arrays: &[Account<TickArray>],
+    tick_current: i32,
+    tick_spacing: u16,
+    is_x: bool,
+) -> Option<(i32, Option<usize>)> {
+    let spacing = tick_spacing as i32;
+    let (first, step) = match is_x {
+        true => (tick_current.div_euclid(spacing) * spacing, -spacing),
+        false => ((tick_current.div_euclid(spacing) + 1) * spacing, spacing),
+    };
+
+    let mut last_covered = None;
+    let mut tick = first;
+    while (cl_math::MIN_TICK..=cl_math::MAX_TICK).contains(&tick) {
+        let Some(index) = tick_arrays.iter().position(|a| a.covers(tick, tick_spacing)) else {
+            break;
+        };
+        if tick_arrays[index].tick(tick, tick_spacing).ok()?.is_initialized() {
+            return Some((tick, Some(index)));
+        }
+        last_covered = Some(tick);
+        tick += step;
+    }
+
+    // Nothing initialized: stop at the edge of what's covered.
+    last_covered.map(|tick| (tick, None))
+}
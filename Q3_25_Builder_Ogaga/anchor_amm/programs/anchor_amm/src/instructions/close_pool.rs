@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{close_account, CloseAccount, Mint, Token, TokenAccount};
+
+use crate::{error::AmmError, events::{PoolClosed, PoolSunset}, state::Config};
+
+use super::token_utils::config_signer_seeds;
+
+#[derive(Accounts)]
+pub struct SunsetPool<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SunsetPool<'info> {
+    // Retiring a pool can't be undone. Swaps and deposits stop for good while
+    // LPs keep withdrawing whenever they like.
+    pub fn sunset_pool(&mut self) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(!self.config.flash_loan_active, AmmError::FlashLoanActive);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+
+        self.config.sunset = true;
+
+        emit!(PoolSunset {
+            config: self.config.key(),
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClosePool<'info> {
+    // Only a pool with no LP outstanding and nothing in its vaults can close,
+    // and its vaults' rent goes back to the authority. MINIMUM_LIQUIDITY is
+    // locked for good on the first deposit, so in practice that is a pool
+    // that never took liquidity; a retired pool that did stays sunset, open
+    // only for withdrawals.
+    //
+    // The config stays behind, marked closed, as does the LP mint, which the
+    // token program can't close. Both addresses derive from the mint pair and
+    // fee tier, so a closed pool's pair and tier can't be initialized again.
+    pub fn close_pool(&mut self) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(self.config.sunset, AmmError::PoolNotSunset);
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(
+            self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0,
+            AmmError::PoolNotEmpty
+        );
+
+        self.close_vault(self.vault_x.to_account_info())?;
+        self.close_vault(self.vault_y.to_account_info())?;
+
+        self.config.closed = true;
+
+        emit!(PoolClosed {
+            config: self.config.key(),
+        });
+
+        Ok(())
+    }
+
+    fn close_vault(&self, account: AccountInfo<'info>) -> Result<()> {
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.authority.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        config_signer_seeds(&self.config, |signer_seeds| {
+            close_account(CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds))
+        })
+    }
+}
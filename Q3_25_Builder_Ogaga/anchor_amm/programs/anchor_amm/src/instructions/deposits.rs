@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{Transfer, transfer, Mint, Token, TokenAccount, MintTo, mint_to}};
-use constant_product_curve::ConstantProduct;
 
-use crate::{state::Config, error::AmmError};
+use crate::{constants::MINIMUM_LIQUIDITY, state::{AllowListEntry, Config}, error::AmmError, events::{LiquidityAdded, Swapped}};
+
+use super::quote_swap;
+use super::token_utils::{config_signer_seeds, transfer_from_vault, unwrap_sol, wrap_sol};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -17,9 +19,10 @@ pub struct Deposit<'info> {
     )]
     pub mint_lp: Account<'info, Mint>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), config.fee.to_le_bytes().as_ref()],
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
@@ -39,18 +42,20 @@ pub struct Deposit<'info> {
     pub vault_y: Account<'info, TokenAccount>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_x,
         associated_token::authority = user
     )]
-    pub user_x: Account<'info, TokenAccount>,
+    pub user_x: Box<Account<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_y,
         associated_token::authority = user
     )]
-    pub user_y: Account<'info, TokenAccount>,
+    pub user_y: Box<Account<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
@@ -60,6 +65,24 @@ pub struct Deposit<'info> {
     )]
     pub user_lp: Account<'info, TokenAccount>,
 
+    // Holds the LP locked on the first deposit. No instruction ever moves
+    // tokens out of it.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = config,
+    )]
+    pub locked_lp: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to this pool and user.
+    #[account(
+        seeds = [b"allow", config.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allow_list_entry: Option<UncheckedAccount<'info>>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -68,36 +91,159 @@ pub struct Deposit<'info> {
 impl<'info> Deposit<'info> {
 
     pub fn deposit(&mut self, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
         require!(amount != 0, AmmError::InvalidAmount);
 
-        let (x, y) = match self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0 {
-            true => (max_x, max_y),
-            false => {
-                let amount = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount, 
-                    self.vault_y.amount, 
-                    self.mint_lp.supply, 
-                    amount, 
-                6).unwrap();
-                (amount.x, amount.y)
-            }
-        };
+        // Tokens sent straight to the vaults don't count: until LP exists
+        // the pool is seeded, and whoever seeds it gets any such donations.
+        if self.mint_lp.supply == 0 {
+            return self.deposit_initial(amount, max_x, max_y);
+        }
+
+        let (x, y) = amm_quote::deposit_amounts(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            amount,
+        )
+        .ok_or(AmmError::Overflow)?;
 
         require!(x<=max_x && y<= max_y, AmmError::SlippageExceeded);
 
         self.deposit_tokens(true, x)?;
         self.deposit_tokens(false, y)?;
 
-        self.mint_lp_tokens(amount)
+        self.mint_lp_tokens(amount)?;
+        self.emit_liquidity_added(x, y, amount)?;
+        self.unwrap_native()
+    }
+
+    // The first deposit sets the pool price, so LP is minted as sqrt(x * y)
+    // (or the StableSwap invariant D) rather than whatever the caller asks
+    // for, and MINIMUM_LIQUIDITY of it is locked away for good. That keeps a
+    // single LP unit from ever being worth enough to inflate the share price
+    // against later depositors.
+    fn deposit_initial(&mut self, min_lp: u64, x: u64, y: u64) -> Result<()> {
+        let curve = self.config.quote_curve(Clock::get()?.unix_timestamp);
+        let liquidity = amm_quote::initial_lp(curve, x, y).ok_or(AmmError::Overflow)?;
+        require!(liquidity > MINIMUM_LIQUIDITY, AmmError::LiquidityLessThanMinimum);
+
+        let lp = liquidity - MINIMUM_LIQUIDITY;
+        require!(lp >= min_lp, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+
+        self.mint_lp_to(self.locked_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+        self.mint_lp_tokens(lp)?;
+        self.emit_liquidity_added(x, y, liquidity)?;
+        self.unwrap_native()
+    }
+
+    pub fn deposit_single(&mut self, is_x: bool, amount_in: u64, min_lp_out: u64) -> Result<()> {
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
+        require!(amount_in != 0, AmmError::InvalidAmount);
+        require!(self.mint_lp.supply != 0, AmmError::NoLiquidityInPool);
+
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.vault_x.amount, self.vault_y.amount),
+            false => (self.vault_y.amount, self.vault_x.amount),
+        };
+
+        let now = Clock::get()?.unix_timestamp;
+        let curve = self.config.quote_curve(now);
+        let swap_amount = amm_quote::quote_zap(curve, reserve_in, reserve_out, amount_in, self.config.swap_fee(now))
+            .ok_or(AmmError::NoLiquidityInPool)?;
+        require!(swap_amount != 0, AmmError::InvalidAmount);
+
+        // Swap part of the input through the pool curve...
+        let res = quote_swap(
+            &self.config,
+            now,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            is_x,
+            swap_amount,
+            0,
+        )?;
+        require!(res.amount_out != 0, AmmError::InvalidAmount);
+
+        self.deposit_tokens(is_x, res.amount_in)?;
+        self.withdraw_tokens(!is_x, res.amount_out)?;
+
+        let before = (self.vault_x.amount, self.vault_y.amount);
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        self.config.record_swap(now, before, (self.vault_x.amount, self.vault_y.amount));
+
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            reserve_x: self.vault_x.amount,
+            reserve_y: self.vault_y.amount,
+        });
+
+        // ...then add the balanced remainder as liquidity.
+        let remaining = amount_in.checked_sub(res.amount_in).ok_or(AmmError::Underflow)?;
+        let (max_x, max_y) = match is_x {
+            true => (remaining, res.amount_out),
+            false => (res.amount_out, remaining),
+        };
+
+        let lp = amm_quote::lp_for_deposit(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            max_x,
+            max_y,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        require!(lp != 0, AmmError::InvalidAmount);
+        require!(lp >= min_lp_out, AmmError::SlippageExceeded);
+
+        // Only take what backs `lp`; rounding dust on either side stays with
+        // the user.
+        let (x, y) = amm_quote::deposit_amounts(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            lp,
+        )
+        .ok_or(AmmError::Overflow)?;
+        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+
+        self.mint_lp_tokens(lp)?;
+        self.emit_liquidity_added(x, y, lp)?;
+        self.unwrap_native()
     }
 
     pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
-        let (from, to) = match is_x {
-            true => (self.user_x.to_account_info(), self.vault_x.to_account_info()),
-            false => (self.user_y.to_account_info(), self.vault_y.to_account_info())
+        let (mint, from, to) = match is_x {
+            true => (self.mint_x.key(), self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.mint_y.key(), self.user_y.to_account_info(), self.vault_y.to_account_info())
         };
 
+        wrap_sol(
+            mint,
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.user.to_account_info(),
+            from.clone(),
+            amount,
+        )?;
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = Transfer {
@@ -111,25 +257,73 @@ impl<'info> Deposit<'info> {
         transfer(ctx, amount)
     }
 
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+        };
+
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.config,
+            self.config.to_account_info(),
+            from,
+            to,
+            amount,
+        )
+    }
+
+    // `x` and `y` have been added to the vaults (as loaded) and `lp_minted`
+    // added to the LP supply.
+    fn emit_liquidity_added(&self, x: u64, y: u64, lp_minted: u64) -> Result<()> {
+        emit!(LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_minted,
+            reserve_x: self.vault_x.amount.checked_add(x).ok_or(AmmError::Overflow)?,
+            reserve_y: self.vault_y.amount.checked_add(y).ok_or(AmmError::Overflow)?,
+            lp_supply: self.mint_lp.supply.checked_add(lp_minted).ok_or(AmmError::Overflow)?,
+        });
+
+        Ok(())
+    }
+
+    // SOL wrapped for this deposit (and anything swapped into wSOL by
+    // deposit_single that wasn't deposited) goes back to the wallet.
+    fn unwrap_native(&self) -> Result<()> {
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.user_x.to_account_info(),
+            self.user.to_account_info(),
+            self.user_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.user_y.to_account_info(),
+            self.user.to_account_info(),
+            self.user_y.amount,
+        )
+    }
+
     pub fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        self.mint_lp_to(self.user_lp.to_account_info(), amount)
+    }
+
+    fn mint_lp_to(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = MintTo {
             mint: self.mint_lp.to_account_info(),
-            to: self.user_lp.to_account_info(),
+            to,
             authority: self.config.to_account_info()
         };
 
-        let seeds = &[
-            &b"config"[..],
-            &self.config.seed.to_le_bytes(),
-            &[self.config.config_bump],
-        ];
-
-        let signer_seeds = &[&seeds[..]];
-
-        let ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-        mint_to(ctx, amount)
+        config_signer_seeds(&self.config, |signer_seeds| {
+            mint_to(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount)
+        })
     }
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::MAX_DYNAMIC_FEE, error::AmmError, state::Config};
+
+#[derive(Accounts)]
+pub struct SetDynamicFee<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetDynamicFee<'info> {
+    pub fn set_dynamic_fee(&mut self, enabled: bool, min_fee: u16, max_fee: u16) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(!self.config.flash_loan_active, AmmError::FlashLoanActive);
+
+        if enabled {
+            require!(min_fee <= max_fee && max_fee <= MAX_DYNAMIC_FEE, AmmError::InvalidDynamicFee);
+        }
+
+        // Switching modes starts the accumulator from scratch.
+        if enabled != self.config.dynamic_fee {
+            self.config.volatility = 0;
+            self.config.last_swap_ts = Clock::get()?.unix_timestamp;
+        }
+
+        self.config.dynamic_fee = enabled;
+        if enabled {
+            self.config.min_fee = min_fee;
+            self.config.max_fee = max_fee;
+        }
+
+        Ok(())
+    }
+}
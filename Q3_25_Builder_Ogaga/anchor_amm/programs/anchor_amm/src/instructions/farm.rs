@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{error::AmmError, state::{Config, Farm, StakePosition}};
+
+use super::token_utils::{transfer_from_farm_vault, transfer_to_vault};
+
+#[derive(Accounts)]
+pub struct InitializeFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm", config.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        space = 8 + Farm::INIT_SPACE,
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub lp_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeFarm<'info> {
+    pub fn init(&mut self, reward_per_second: u64, bumps: InitializeFarmBumps) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            reward_mint: self.reward_mint.key(),
+            reward_per_second,
+            reward_per_share: 0,
+            rewards_unallocated: 0,
+            total_staked: 0,
+            last_update_ts: Clock::get()?.unix_timestamp,
+            bump: bumps.farm,
+        });
+
+        Ok(())
+    }
+}
+
+// Tops up a farm's rewards and sets how fast they are emitted from now on.
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref(), reward_mint.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = authority,
+    )]
+    pub authority_reward: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundFarm<'info> {
+    pub fn fund_farm(&mut self, amount: u64, reward_per_second: u64) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+
+        // Everything up to now is emitted at the old rate.
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        self.farm.rewards_unallocated = self
+            .farm
+            .rewards_unallocated
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+        self.farm.reward_per_second = reward_per_second;
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            self.authority_reward.to_account_info(),
+            self.reward_vault.to_account_info(),
+            self.authority.to_account_info(),
+            amount,
+        )
+    }
+}
+
+// Shared by stake_lp, unstake_lp and harvest.
+#[derive(Accounts)]
+pub struct ManageStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref(), reward_mint.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"stake", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + StakePosition::INIT_SPACE,
+    )]
+    pub stake: Box<Account<'info, StakePosition>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub lp_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ManageStake<'info> {
+    pub fn stake_lp(&mut self, amount: u64, bumps: ManageStakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let delta = i64::try_from(amount).map_err(|_| AmmError::Overflow)?;
+        self.settle(delta, bumps)?;
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            self.user_lp.to_account_info(),
+            self.lp_vault.to_account_info(),
+            self.user.to_account_info(),
+            amount,
+        )
+    }
+
+    pub fn unstake_lp(&mut self, amount: u64, bumps: ManageStakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(amount <= self.stake.amount, AmmError::InsufficientBalance);
+
+        let delta = i64::try_from(amount).map_err(|_| AmmError::Overflow)?;
+        self.settle(-delta, bumps)?;
+
+        transfer_from_farm_vault(
+            self.token_program.to_account_info(),
+            &self.farm,
+            self.farm.to_account_info(),
+            self.lp_vault.to_account_info(),
+            self.user_lp.to_account_info(),
+            amount,
+        )
+    }
+
+    pub fn harvest(&mut self, bumps: ManageStakeBumps) -> Result<()> {
+        self.settle(0, bumps)?;
+
+        let owed = self.stake.rewards_owed;
+        require!(owed != 0, AmmError::InvalidAmount);
+        self.stake.rewards_owed = 0;
+
+        transfer_from_farm_vault(
+            self.token_program.to_account_info(),
+            &self.farm,
+            self.farm.to_account_info(),
+            self.reward_vault.to_account_info(),
+            self.user_reward.to_account_info(),
+            owed,
+        )
+    }
+
+    // Brings the farm up to date, then credits the user's rewards and moves
+    // their stake by `amount_delta`.
+    fn settle(&mut self, amount_delta: i64, bumps: ManageStakeBumps) -> Result<()> {
+        if self.stake.owner == Pubkey::default() {
+            self.stake.owner = self.user.key();
+            self.stake.farm = self.farm.key();
+            self.stake.bump = bumps.stake;
+        }
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.stake.settle(self.farm.reward_per_share, amount_delta)?;
+
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_add_signed(amount_delta)
+            .ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::{prelude::*, solana_program::{instruction::{AccountMeta, Instruction}, program::invoke}};
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{error::AmmError, state::{AllowListEntry, Config}};
+
+use super::token_utils::{transfer_from_vault, unwrap_sol};
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    #[account(
+        constraint = mint.key() == config.mint_x || mint.key() == config.mint_y @ AmmError::InvalidToken,
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        associated_token::mint = mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_ata: Account<'info, TokenAccount>,
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to this pool and borrower.
+    #[account(
+        seeds = [b"allow", config.key().as_ref(), borrower.key().as_ref()],
+        bump,
+    )]
+    pub allow_list_entry: Option<UncheckedAccount<'info>>,
+    /// CHECK: Any executable program other than this one. It receives the
+    /// remaining accounts and `data`, and must repay the vault before returning.
+    #[account(
+        executable,
+        constraint = receiver.key() != crate::ID @ AmmError::InvalidFlashLoanReceiver,
+    )]
+    pub receiver: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FlashLoan<'info> {
+    pub fn flash_loan(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        amount: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(amount <= self.vault.amount, AmmError::InsufficientBalance);
+
+        // Loans pay what a swap would right now, so a dynamic-fee pool charges
+        // its volatility-scaled fee.
+        let now = Clock::get()?.unix_timestamp;
+        let fee = amm_quote::flash_loan_fee(amount, self.config.swap_fee(now)).ok_or(AmmError::Overflow)?;
+        let owed = self.vault.amount.checked_add(fee).ok_or(AmmError::Overflow)?;
+
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.config,
+            self.config.to_account_info(),
+            self.vault.to_account_info(),
+            self.borrower_ata.to_account_info(),
+            amount,
+        )?;
+
+        // Flag the loan while the receiver runs so the borrowed tokens can't be
+        // used against this pool's own (temporarily reduced) reserves, and the
+        // authority can't reconfigure the pool mid-loan.
+        self.config.flash_loan_active = true;
+        self.config.exit(&crate::ID)?;
+
+        self.invoke_receiver(remaining_accounts, data)?;
+
+        // Write back what the account holds now, not the copy from before the
+        // receiver ran.
+        self.config.reload()?;
+        self.config.flash_loan_active = false;
+
+        self.vault.reload()?;
+        require!(self.vault.amount >= owed, AmmError::FlashLoanNotRepaid);
+
+        // Whatever the receiver leaves in a native borrower account comes back
+        // to the borrower as SOL.
+        unwrap_sol(
+            self.mint.key(),
+            self.token_program.to_account_info(),
+            self.borrower_ata.to_account_info(),
+            self.borrower.to_account_info(),
+            self.borrower_ata.amount,
+        )
+    }
+
+    fn invoke_receiver(&self, remaining_accounts: &'info [AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let accounts = remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+
+        let ix = Instruction {
+            program_id: self.receiver.key(),
+            accounts,
+            data,
+        };
+
+        let mut account_infos = remaining_accounts.to_vec();
+        account_infos.push(self.receiver.to_account_info());
+
+        invoke(&ix, &account_infos)?;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
 
-use crate::state::Config;
+use crate::{constants::{FEE_TIERS, MAX_AMP, MIN_AMP}, error::AmmError, events::PoolInitialized, state::{Config, CurveType}};
 
 
 
 #[derive(Accounts)]
-#[instruction(seed: u64)]
+#[instruction(fee: u16)]
 pub struct Initialize <'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
@@ -38,7 +38,7 @@ pub struct Initialize <'info> {
     #[account(
         init,
         payer = initializer,
-        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), fee.to_le_bytes().as_ref()],
         bump,
         space = Config::INIT_SPACE,
     )]
@@ -49,9 +49,27 @@ pub struct Initialize <'info> {
 }
 
 impl<'info> Initialize<'info> {
-    pub fn init(&mut self, seed: u64, fee: u16, authority: Option<Pubkey>, bumps: InitializeBumps) -> Result<()> {
+    pub fn init(&mut self, fee: u16, authority: Option<Pubkey>, curve: CurveType, amp: u64, allow_list: bool, bumps: InitializeBumps) -> Result<()> {
+        require!(self.mint_x.key() != self.mint_y.key(), AmmError::IdenticalMints);
+        // Only one ordering of a pair is accepted, so every pair and fee tier
+        // maps to exactly one config PDA.
+        require!(self.mint_x.key() < self.mint_y.key(), AmmError::UnsortedMints);
+        require!(FEE_TIERS.contains(&fee), AmmError::InvalidFeeTier);
+        // Someone has to be able to manage the list.
+        require!(!allow_list || authority.is_some(), AmmError::NoAuthoritySet);
+
+        let amp = match curve {
+            CurveType::ConstantProduct => 0,
+            CurveType::StableSwap => {
+                require!((MIN_AMP..=MAX_AMP).contains(&amp), AmmError::InvalidAmp);
+                // The invariant compares raw reserves, so both sides need to
+                // be denominated the same way.
+                require!(self.mint_x.decimals == self.mint_y.decimals, AmmError::DecimalsMismatch);
+                amp
+            }
+        };
+
         self.config.set_inner(Config {
-            seed,
             authority,
             mint_x: self.mint_x.key(),
             mint_y: self.mint_y.key(),
@@ -59,6 +77,32 @@ impl<'info> Initialize<'info> {
             locked: false,
             config_bump: bumps.config,
             lp_bump: bumps.mint_lp,
+            curve,
+            initial_amp: amp,
+            target_amp: amp,
+            ramp_start_ts: 0,
+            ramp_stop_ts: 0,
+            allow_list,
+            dynamic_fee: false,
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            last_swap_ts: 0,
+            sunset: false,
+            closed: false,
+            flash_loan_active: false,
+        });
+
+        emit!(PoolInitialized {
+            config: self.config.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            mint_lp: self.mint_lp.key(),
+            fee,
+            curve,
+            amp,
+            authority,
+            allow_list,
         });
 
         Ok(())
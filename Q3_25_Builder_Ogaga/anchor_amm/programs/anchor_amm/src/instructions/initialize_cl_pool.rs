@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{cl_math, constants::{FEE_TIERS, TICK_SPACINGS}, error::AmmError, state::{ClPool, TickArray, Tick, TICK_ARRAY_SIZE}};
+
+#[derive(Accounts)]
+#[instruction(fee: u16)]
+pub struct InitializeClPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"cl_pool", mint_x.key().as_ref(), mint_y.key().as_ref(), fee.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + ClPool::INIT_SPACE,
+    )]
+    pub pool: Account<'info, ClPool>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeClPool<'info> {
+    pub fn init(&mut self, fee: u16, sqrt_price: u128, bumps: InitializeClPoolBumps) -> Result<()> {
+        require!(self.mint_x.key() != self.mint_y.key(), AmmError::IdenticalMints);
+        require!(self.mint_x.key() < self.mint_y.key(), AmmError::UnsortedMints);
+
+        let tier = FEE_TIERS
+            .iter()
+            .position(|tier| *tier == fee)
+            .ok_or(AmmError::InvalidFeeTier)?;
+        let tick_current = cl_math::tick_at_sqrt_price(sqrt_price).ok_or(AmmError::InvalidSqrtPrice)?;
+
+        self.pool.set_inner(ClPool {
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            tick_spacing: TICK_SPACINGS[tier],
+            sqrt_price,
+            tick_current,
+            liquidity: 0,
+            fee_growth_global_x: 0,
+            fee_growth_global_y: 0,
+            bump: bumps.pool,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"cl_pool", pool.mint_x.as_ref(), pool.mint_y.as_ref(), pool.fee.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ClPool>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"tick_array", pool.key().as_ref(), start_tick_index.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + TickArray::INIT_SPACE,
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeTickArray<'info> {
+    pub fn init(&mut self, start_tick_index: i32) -> Result<()> {
+        require!(
+            TickArray::start_index_for(start_tick_index, self.pool.tick_spacing) == start_tick_index,
+            AmmError::InvalidTickArray
+        );
+        require!(
+            (cl_math::MIN_TICK..=cl_math::MAX_TICK).contains(&start_tick_index)
+                || TickArray::start_index_for(cl_math::MIN_TICK, self.pool.tick_spacing) == start_tick_index,
+            AmmError::InvalidTickArray
+        );
+
+        self.tick_array.set_inner(TickArray {
+            pool: self.pool.key(),
+            start_tick_index,
+            ticks: [Tick::default(); TICK_ARRAY_SIZE],
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{associated_token::{get_associated_token_address, AssociatedToken}, token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, events::Swapped, state::{AllowListEntry, Config, LimitOrder}};
+
+use super::quote_swap;
+use super::token_utils::{transfer_from_vault, transfer_to_vault};
+
+// Remaining accounts for fill_orders are laid out as:
+//   [(order, order_vault, owner_out, owner) * orders]
+const ACCOUNTS_PER_ORDER: usize = 4;
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(constraint = mint_in.key() != mint_out.key() @ AmmError::InvalidToken)]
+    pub mint_in: Box<Account<'info, Mint>>,
+    pub mint_out: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"order", config.key().as_ref(), owner.key().as_ref(), order_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + LimitOrder::INIT_SPACE,
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub order_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Box<Account<'info, TokenAccount>>,
+    // Created up front so a crank can always pay the order out.
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_out,
+        associated_token::authority = owner,
+    )]
+    pub owner_out: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to this pool and owner.
+    #[account(
+        seeds = [b"allow", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub allow_list_entry: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    pub fn place_order(
+        &mut self,
+        order_id: u64,
+        amount_in: u64,
+        min_amount_out: u64,
+        tip: u64,
+        expires_at: i64,
+        bumps: PlaceOrderBumps,
+    ) -> Result<()> {
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
+        require!(amount_in != 0 && min_amount_out != 0, AmmError::InvalidAmount);
+        require!(expires_at > Clock::get()?.unix_timestamp, AmmError::InvalidOrder);
+
+        let is_x = match (self.mint_in.key(), self.mint_out.key()) {
+            (mint_in, mint_out) if mint_in == self.config.mint_x && mint_out == self.config.mint_y => true,
+            (mint_in, mint_out) if mint_in == self.config.mint_y && mint_out == self.config.mint_x => false,
+            _ => return err!(AmmError::InvalidToken),
+        };
+
+        self.order.set_inner(LimitOrder {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            order_id,
+            is_x,
+            amount_in,
+            min_amount_out,
+            tip,
+            expires_at,
+            bump: bumps.order,
+        });
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            self.owner_in.to_account_info(),
+            self.order_vault.to_account_info(),
+            self.owner.to_account_info(),
+            amount_in,
+        )?;
+
+        // The tip rides along in the order account until it is filled.
+        if tip != 0 {
+            let accounts = system_program::Transfer {
+                from: self.owner.to_account_info(),
+                to: self.order.to_account_info(),
+            };
+            system_program::transfer(CpiContext::new(self.system_program.to_account_info(), accounts), tip)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The owner can cancel at any time; anyone can clean up an expired order.
+// Either way the input, rent and tip go back to the owner.
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    pub signer: Signer<'info>,
+    /// CHECK: Checked against the order below; receives the refunds.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    pub mint_in: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = owner,
+        close = owner,
+        seeds = [b"order", order.config.as_ref(), owner.key().as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump = order.bump,
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub order_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelOrder<'info> {
+    pub fn cancel_order(&mut self) -> Result<()> {
+        if self.signer.key() != self.owner.key() {
+            require!(Clock::get()?.unix_timestamp >= self.order.expires_at, AmmError::OrderNotExpired);
+        }
+
+        let order_info = self.order.to_account_info();
+        transfer_from_order_vault(
+            self.token_program.to_account_info(),
+            &self.order,
+            order_info.clone(),
+            self.order_vault.to_account_info(),
+            self.owner_in.to_account_info(),
+            self.order_vault.amount,
+        )?;
+        close_order_vault(
+            self.token_program.to_account_info(),
+            &self.order,
+            order_info,
+            self.order_vault.to_account_info(),
+            self.owner.to_account_info(),
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct FillOrders<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FillOrders<'info> {
+    // Fills every order whose limit the pool price has reached, in the order
+    // given. Orders that can't be filled yet, have expired, or whose owner has
+    // closed the account the output goes to are skipped.
+    pub fn fill_orders(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.len().is_multiple_of(ACCOUNTS_PER_ORDER),
+            AmmError::InvalidOrder
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        for accounts in remaining_accounts.chunks(ACCOUNTS_PER_ORDER) {
+            self.fill_order(accounts, now)?;
+        }
+
+        Ok(())
+    }
+
+    fn fill_order(&mut self, accounts: &'info [AccountInfo<'info>], now: i64) -> Result<()> {
+        let order = Account::<LimitOrder>::try_from(&accounts[0])?;
+        let (order_vault, owner_out, owner) = (&accounts[1], &accounts[2], &accounts[3]);
+
+        let (mint_in, mint_out) = match order.is_x {
+            true => (self.config.mint_x, self.config.mint_y),
+            false => (self.config.mint_y, self.config.mint_x),
+        };
+        require_keys_eq!(order.config, self.config.key(), AmmError::InvalidOrder);
+        require_keys_eq!(order_vault.key(), get_associated_token_address(&order.key(), &mint_in), AmmError::InvalidOrder);
+        require_keys_eq!(owner_out.key(), get_associated_token_address(&order.owner, &mint_out), AmmError::InvalidOrder);
+        require_keys_eq!(owner.key(), order.owner, AmmError::InvalidOrder);
+
+        if now >= order.expires_at || owner_out.data_is_empty() {
+            return Ok(());
+        }
+
+        let before = (self.vault_x.amount, self.vault_y.amount);
+        let res = quote_swap(&self.config, now, before.0, before.1, order.is_x, order.amount_in, 0)?;
+        if res.amount_out < order.min_amount_out {
+            return Ok(());
+        }
+
+        let (vault_in, vault_out) = match order.is_x {
+            true => (self.vault_x.to_account_info(), self.vault_y.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.vault_x.to_account_info()),
+        };
+
+        // The vault has to be empty to close. Anything sent to it on top of
+        // the order's input goes to the pool along with it.
+        let vault_balance = Account::<TokenAccount>::try_from(order_vault)?.amount;
+        transfer_from_order_vault(
+            self.token_program.to_account_info(),
+            &order,
+            order.to_account_info(),
+            order_vault.clone(),
+            vault_in,
+            vault_balance,
+        )?;
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.config,
+            self.config.to_account_info(),
+            vault_out,
+            owner_out.clone(),
+            res.amount_out,
+        )?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        let after = (self.vault_x.amount, self.vault_y.amount);
+        self.config.record_swap(now, before, after);
+
+        emit!(Swapped {
+            config: self.config.key(),
+            user: order.owner,
+            is_x: order.is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            reserve_x: after.0,
+            reserve_y: after.1,
+        });
+
+        // Pay the cranker, then hand the rest of the order's lamports back.
+        let order_info = order.to_account_info();
+        let order_lamports = order_info.lamports().checked_sub(order.tip).ok_or(AmmError::Underflow)?;
+        let cranker_lamports = self.cranker.lamports().checked_add(order.tip).ok_or(AmmError::Overflow)?;
+        **order_info.try_borrow_mut_lamports()? = order_lamports;
+        **self.cranker.to_account_info().try_borrow_mut_lamports()? = cranker_lamports;
+
+        close_order_vault(
+            self.token_program.to_account_info(),
+            &order,
+            order_info,
+            order_vault.clone(),
+            owner.clone(),
+        )?;
+        order.close(owner.clone())
+    }
+}
+
+// Moves `amount` out of an order's vault, signing with the order PDA.
+fn transfer_from_order_vault<'info>(
+    token_program: AccountInfo<'info>,
+    order: &LimitOrder,
+    order_info: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: order_info,
+    };
+
+    let order_id = order.order_id.to_le_bytes();
+    let seeds = &[
+        &b"order"[..],
+        order.config.as_ref(),
+        order.owner.as_ref(),
+        &order_id,
+        &[order.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    transfer(CpiContext::new_with_signer(token_program, accounts, signer_seeds), amount)
+}
+
+// Closes an emptied order vault, sending its rent to `destination`.
+fn close_order_vault<'info>(
+    token_program: AccountInfo<'info>,
+    order: &LimitOrder,
+    order_info: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    let accounts = CloseAccount {
+        account: vault,
+        destination,
+        authority: order_info,
+    };
+
+    let order_id = order.order_id.to_le_bytes();
+    let seeds = &[
+        &b"order"[..],
+        order.config.as_ref(),
+        order.owner.as_ref(),
+        &order_id,
+        &[order.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    close_account(CpiContext::new_with_signer(token_program, accounts, signer_seeds))
+}
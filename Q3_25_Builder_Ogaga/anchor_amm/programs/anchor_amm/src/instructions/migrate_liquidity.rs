@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{burn, mint_to, Burn, Mint, MintTo, Token, TokenAccount}};
+
+use crate::{error::AmmError, events::{LiquidityAdded, LiquidityRemoved}, state::{AllowListEntry, Config}};
+
+use super::token_utils::{config_signer_seeds, transfer_from_vault, transfer_to_vault, unwrap_sol};
+
+// Moves liquidity between two pools for the same mint pair (e.g. out of a
+// sunset pool into another fee tier) without the tokens resting in the
+// wallet in between. Whatever doesn't fit the new pool's ratio stays in
+// `user_x` / `user_y`.
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), old_config.fee.to_le_bytes().as_ref()],
+        bump = old_config.config_bump,
+    )]
+    pub old_config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"lp", old_config.key().as_ref()],
+        bump = old_config.lp_bump,
+    )]
+    pub old_mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = old_config,
+    )]
+    pub old_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = old_config,
+    )]
+    pub old_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = old_mint_lp,
+        associated_token::authority = user,
+    )]
+    pub old_user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = new_config.key() != old_config.key() @ AmmError::InvalidRoute,
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), new_config.fee.to_le_bytes().as_ref()],
+        bump = new_config.config_bump,
+    )]
+    pub new_config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"lp", new_config.key().as_ref()],
+        bump = new_config.lp_bump,
+    )]
+    pub new_mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = new_config,
+    )]
+    pub new_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = new_config,
+    )]
+    pub new_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = new_mint_lp,
+        associated_token::authority = user,
+    )]
+    pub new_user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to the old pool and user.
+    #[account(
+        seeds = [b"allow", old_config.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub old_allow_list_entry: Option<UncheckedAccount<'info>>,
+    /// CHECK: As above, for the new pool.
+    #[account(
+        seeds = [b"allow", new_config.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub new_allow_list_entry: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateLiquidity<'info> {
+    // `min_x` and `min_y` bound what comes out of the old pool, as for a
+    // withdrawal, and `min_lp_out` what the new pool mints for it.
+    pub fn migrate_liquidity(&mut self, amount: u64, min_x: u64, min_y: u64, min_lp_out: u64) -> Result<()> {
+        require!(!self.old_config.is_locked() && !self.new_config.is_locked(), AmmError::PoolLocked);
+        require!(!self.new_config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.old_config, self.old_allow_list_entry.as_deref())?;
+        AllowListEntry::check(&self.new_config, self.new_allow_list_entry.as_deref())?;
+        require!(amount != 0, AmmError::InvalidAmount);
+        // The first deposit into a pool sets its price; that isn't something
+        // to do as a side effect of a migration.
+        require!(self.new_mint_lp.supply != 0, AmmError::NoLiquidityInPool);
+
+        // Withdraw from the old pool...
+        let (x, y) = amm_quote::withdraw_amounts(
+            self.old_vault_x.amount,
+            self.old_vault_y.amount,
+            self.old_mint_lp.supply,
+            amount,
+        )
+        .ok_or(AmmError::InsufficientBalance)?;
+        require!(x >= min_x && y >= min_y, AmmError::SlippageExceeded);
+
+        self.withdraw_old(self.old_vault_x.to_account_info(), self.user_x.to_account_info(), x)?;
+        self.withdraw_old(self.old_vault_y.to_account_info(), self.user_y.to_account_info(), y)?;
+        self.burn_old_lp(amount)?;
+
+        emit!(LiquidityRemoved {
+            config: self.old_config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_burned: amount,
+            reserve_x: self.old_vault_x.amount - x,
+            reserve_y: self.old_vault_y.amount - y,
+            lp_supply: self.old_mint_lp.supply - amount,
+        });
+
+        // ...and deposit as much of it as the new pool's ratio takes.
+        let lp = amm_quote::lp_for_deposit(
+            self.new_vault_x.amount,
+            self.new_vault_y.amount,
+            self.new_mint_lp.supply,
+            x,
+            y,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        require!(lp != 0, AmmError::InvalidAmount);
+        require!(lp >= min_lp_out, AmmError::SlippageExceeded);
+
+        let (dx, dy) = amm_quote::deposit_amounts(
+            self.new_vault_x.amount,
+            self.new_vault_y.amount,
+            self.new_mint_lp.supply,
+            lp,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        self.deposit_new(self.user_x.to_account_info(), self.new_vault_x.to_account_info(), dx)?;
+        self.deposit_new(self.user_y.to_account_info(), self.new_vault_y.to_account_info(), dy)?;
+        self.mint_new_lp(lp)?;
+
+        emit!(LiquidityAdded {
+            config: self.new_config.key(),
+            user: self.user.key(),
+            amount_x: dx,
+            amount_y: dy,
+            lp_minted: lp,
+            reserve_x: self.new_vault_x.amount.checked_add(dx).ok_or(AmmError::Overflow)?,
+            reserve_y: self.new_vault_y.amount.checked_add(dy).ok_or(AmmError::Overflow)?,
+            lp_supply: self.new_mint_lp.supply.checked_add(lp).ok_or(AmmError::Overflow)?,
+        });
+
+        // A native side's leftover is paid out as SOL.
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.user_x.to_account_info(),
+            self.user.to_account_info(),
+            self.user_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.user_y.to_account_info(),
+            self.user.to_account_info(),
+            self.user_y.amount,
+        )
+    }
+
+    fn withdraw_old(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.old_config,
+            self.old_config.to_account_info(),
+            from,
+            to,
+            amount,
+        )
+    }
+
+    fn deposit_new(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            from,
+            to,
+            self.user.to_account_info(),
+            amount,
+        )
+    }
+
+    fn burn_old_lp(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint: self.old_mint_lp.to_account_info(),
+            from: self.old_user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        burn(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    fn mint_new_lp(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: self.new_mint_lp.to_account_info(),
+            to: self.new_user_lp.to_account_info(),
+            authority: self.new_config.to_account_info(),
+        };
+
+        config_signer_seeds(&self.new_config, |signer_seeds| {
+            mint_to(CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds), amount)
+        })
+    }
+}
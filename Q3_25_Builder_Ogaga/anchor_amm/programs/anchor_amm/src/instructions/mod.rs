@@ -2,8 +2,33 @@ pub mod initialize;
 pub mod deposits;
 pub mod withdrawals;
 pub mod swaps;
+pub mod route_swap;
+pub mod flash_loan;
+pub mod ramp_amp;
+pub mod dynamic_fee;
+pub mod allow_list;
+pub mod farm;
+pub mod limit_orders;
+pub mod close_pool;
+pub mod migrate_liquidity;
+pub mod initialize_cl_pool;
+pub mod positions;
+pub mod cl_swap;
+pub mod token_utils;
 
 pub use initialize::*;
 pub use deposits::*;
 pub use withdrawals::*;
-pub use swaps::*;
\ No newline at end of file
+pub use swaps::*;
+pub use route_swap::*;
+pub use flash_loan::*;
+pub use ramp_amp::*;
+pub use dynamic_fee::*;
+pub use allow_list::*;
+pub use farm::*;
+pub use limit_orders::*;
+pub use close_pool::*;
+pub use migrate_liquidity::*;
+pub use initialize_cl_pool::*;
+pub use positions::*;
+pub use cl_swap::*;
\ No newline at end of file
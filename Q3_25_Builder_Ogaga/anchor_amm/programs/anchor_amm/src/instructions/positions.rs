@@ -0,0 +1,316 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{cl_math, error::AmmError, state::{ClPool, Position, TickArray}};
+
+use super::token_utils::{transfer_from_cl_vault, transfer_to_vault, unwrap_sol, wrap_sol};
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"cl_pool", pool.mint_x.as_ref(), pool.mint_y.as_ref(), pool.fee.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ClPool>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"position", pool.key().as_ref(), owner.key().as_ref(), tick_lower.to_le_bytes().as_ref(), tick_upper.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Position::INIT_SPACE,
+    )]
+    pub position: Account<'info, Position>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    pub fn open_position(&mut self, tick_lower: i32, tick_upper: i32, bumps: OpenPositionBumps) -> Result<()> {
+        let tick_spacing = self.pool.tick_spacing as i32;
+
+        require!(tick_lower < tick_upper, AmmError::InvalidTickRange);
+        require!(tick_lower >= cl_math::MIN_TICK && tick_upper <= cl_math::MAX_TICK, AmmError::InvalidTickRange);
+        require!(tick_lower % tick_spacing == 0 && tick_upper % tick_spacing == 0, AmmError::InvalidTickRange);
+
+        self.position.set_inner(Position {
+            owner: self.owner.key(),
+            pool: self.pool.key(),
+            tick_lower,
+            tick_upper,
+            liquidity: 0,
+            fee_growth_inside_last_x: 0,
+            fee_growth_inside_last_y: 0,
+            tokens_owed_x: 0,
+            tokens_owed_y: 0,
+            bump: bumps.position,
+        });
+
+        Ok(())
+    }
+}
+
+// Shared by increase_liquidity, decrease_liquidity and collect_fees. When both
+// of the position's ticks live in the same tick array, pass it twice.
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"cl_pool", mint_x.key().as_ref(), mint_y.key().as_ref(), pool.fee.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ClPool>>,
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = pool,
+        seeds = [b"position", pool.key().as_ref(), owner.key().as_ref(), position.tick_lower.to_le_bytes().as_ref(), position.tick_upper.to_le_bytes().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(mut, has_one = pool)]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+    #[account(mut, has_one = pool)]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = pool,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = pool,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    pub fn increase_liquidity(&mut self, liquidity: u128, max_x: u64, max_y: u64) -> Result<()> {
+        require!(liquidity != 0, AmmError::InvalidAmount);
+
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        self.modify(delta)?;
+
+        let (x, y) = cl_math::amounts_for_liquidity(
+            self.pool.sqrt_price,
+            self.position.tick_lower,
+            self.position.tick_upper,
+            liquidity,
+            true,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+        self.unwrap_native()
+    }
+
+    pub fn decrease_liquidity(&mut self, liquidity: u128, min_x: u64, min_y: u64) -> Result<()> {
+        require!(liquidity != 0, AmmError::InvalidAmount);
+        require!(liquidity <= self.position.liquidity, AmmError::InsufficientBalance);
+
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        self.modify(-delta)?;
+
+        let (x, y) = cl_math::amounts_for_liquidity(
+            self.pool.sqrt_price,
+            self.position.tick_lower,
+            self.position.tick_upper,
+            liquidity,
+            false,
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        require!(x >= min_x && y >= min_y, AmmError::SlippageExceeded);
+
+        self.withdraw_tokens(true, x)?;
+        self.withdraw_tokens(false, y)?;
+        self.unwrap_native()
+    }
+
+    pub fn collect_fees(&mut self) -> Result<()> {
+        self.modify(0)?;
+
+        let (x, y) = (self.position.tokens_owed_x, self.position.tokens_owed_y);
+        self.position.tokens_owed_x = 0;
+        self.position.tokens_owed_y = 0;
+
+        self.withdraw_tokens(true, x)?;
+        self.withdraw_tokens(false, y)?;
+        self.unwrap_native()
+    }
+
+    // Applies `liquidity_delta` to the position, its two ticks and (if the
+    // range is active) the pool, settling fees earned so far first. Ticks the
+    // change empties are only cleared once those fees are settled, as their
+    // fee growth is still needed to compute them.
+    fn modify(&mut self, liquidity_delta: i128) -> Result<()> {
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+        let tick_spacing = self.pool.tick_spacing;
+        let same_array = self.tick_array_lower.key() == self.tick_array_upper.key();
+
+        let (mut flipped_lower, mut flipped_upper) = (false, false);
+        if liquidity_delta != 0 {
+            let pool = &self.pool;
+
+            flipped_lower = self.tick_array_lower.tick_mut(tick_lower, tick_spacing)?.update(
+                tick_lower,
+                pool.tick_current,
+                liquidity_delta,
+                false,
+                pool.fee_growth_global_x,
+                pool.fee_growth_global_y,
+            )?;
+
+            let upper_array = match same_array {
+                true => &mut self.tick_array_lower,
+                false => &mut self.tick_array_upper,
+            };
+            flipped_upper = upper_array.tick_mut(tick_upper, tick_spacing)?.update(
+                tick_upper,
+                pool.tick_current,
+                liquidity_delta,
+                true,
+                pool.fee_growth_global_x,
+                pool.fee_growth_global_y,
+            )?;
+        }
+
+        let lower = *self.tick_array_lower.tick(tick_lower, tick_spacing)?;
+        let upper = match same_array {
+            true => *self.tick_array_lower.tick(tick_upper, tick_spacing)?,
+            false => *self.tick_array_upper.tick(tick_upper, tick_spacing)?,
+        };
+
+        let (inside_x, inside_y) = self.pool.fee_growth_inside(tick_lower, &lower, tick_upper, &upper);
+        self.position.accrue_fees(inside_x, inside_y)?;
+
+        if liquidity_delta < 0 {
+            if flipped_lower {
+                self.tick_array_lower.tick_mut(tick_lower, tick_spacing)?.clear();
+            }
+            if flipped_upper {
+                let upper_array = match same_array {
+                    true => &mut self.tick_array_lower,
+                    false => &mut self.tick_array_upper,
+                };
+                upper_array.tick_mut(tick_upper, tick_spacing)?.clear();
+            }
+        }
+
+        self.position.liquidity = self
+            .position
+            .liquidity
+            .checked_add_signed(liquidity_delta)
+            .ok_or(AmmError::Overflow)?;
+
+        if (tick_lower..tick_upper).contains(&self.pool.tick_current) {
+            self.pool.liquidity = self
+                .pool
+                .liquidity
+                .checked_add_signed(liquidity_delta)
+                .ok_or(AmmError::Overflow)?;
+        }
+
+        // Both fields are written back on exit; keep the copies identical so
+        // neither overwrites the other's tick updates.
+        if same_array {
+            self.tick_array_upper.ticks = self.tick_array_lower.ticks;
+        }
+
+        Ok(())
+    }
+
+    fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (mint, from, to) = match is_x {
+            true => (self.mint_x.key(), self.owner_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.mint_y.key(), self.owner_y.to_account_info(), self.vault_y.to_account_info()),
+        };
+
+        wrap_sol(
+            mint,
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.owner.to_account_info(),
+            from.clone(),
+            amount,
+        )?;
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            from,
+            to,
+            self.owner.to_account_info(),
+            amount,
+        )
+    }
+
+    fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.owner_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.owner_y.to_account_info()),
+        };
+
+        transfer_from_cl_vault(
+            self.token_program.to_account_info(),
+            &self.pool,
+            self.pool.to_account_info(),
+            from,
+            to,
+            amount,
+        )
+    }
+
+    fn unwrap_native(&self) -> Result<()> {
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.owner_x.to_account_info(),
+            self.owner.to_account_info(),
+            self.owner_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.owner_y.to_account_info(),
+            self.owner.to_account_info(),
+            self.owner_y.amount,
+        )
+    }
+}
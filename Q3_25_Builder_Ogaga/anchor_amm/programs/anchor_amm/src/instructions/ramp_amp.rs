@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION}, error::AmmError, state::{Config, CurveType}};
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> RampAmp<'info> {
+    pub fn ramp_amp(&mut self, target_amp: u64, ramp_stop_ts: i64) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(!self.config.flash_loan_active, AmmError::FlashLoanActive);
+        require!(self.config.curve == CurveType::StableSwap, AmmError::InvalidCurve);
+        require!((MIN_AMP..=MAX_AMP).contains(&target_amp), AmmError::InvalidAmp);
+
+        let now = Clock::get()?.unix_timestamp;
+        // Ramps can't be interrupted; a new one starts from wherever the
+        // previous one ended.
+        require!(now >= self.config.ramp_stop_ts, AmmError::InvalidAmpRamp);
+        require!(
+            ramp_stop_ts >= now.checked_add(MIN_RAMP_DURATION).ok_or(AmmError::Overflow)?,
+            AmmError::InvalidAmpRamp
+        );
+
+        let current_amp = self.config.amp(now);
+        let within_bounds = match target_amp >= current_amp {
+            true => target_amp <= current_amp.saturating_mul(MAX_AMP_CHANGE),
+            false => target_amp.saturating_mul(MAX_AMP_CHANGE) >= current_amp,
+        };
+        require!(within_bounds, AmmError::InvalidAmpRamp);
+
+        self.config.initial_amp = current_amp;
+        self.config.target_amp = target_amp;
+        self.config.ramp_start_ts = now;
+        self.config.ramp_stop_ts = ramp_stop_ts;
+
+        Ok(())
+    }
+}
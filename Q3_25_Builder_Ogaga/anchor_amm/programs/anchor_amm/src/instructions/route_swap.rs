@@ -0,0 +1,218 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create, get_associated_token_address, AssociatedToken, Create},
+    token::{spl_token::native_mint, Mint, Token, TokenAccount},
+};
+
+use crate::{error::AmmError, events::Swapped, state::{AllowListEntry, Config}};
+
+use super::{invariant_holds, quote_swap};
+use super::token_utils::{transfer_from_vault, transfer_to_vault, unwrap_sol, wrap_sol};
+
+// Remaining accounts for a route are laid out as:
+//   [user_in, (config, vault_x, vault_y, user_out, allow_list_entry) * hops]
+// where each hop's `user_out` is also the next hop's input account. The
+// user's allow-list entry PDA for the hop's pool is only read on allow-listed
+// pools, but is always passed to keep the layout fixed.
+const ACCOUNTS_PER_HOP: usize = 5;
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    // Only used to create the user's wSOL account for a native input.
+    #[account(address = native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RouteSwap<'info> {
+    pub fn route_swap(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        let (user_in, hops) = remaining_accounts
+            .split_first()
+            .ok_or(AmmError::InvalidRoute)?;
+        require!(
+            !hops.is_empty() && hops.len() % ACCOUNTS_PER_HOP == 0,
+            AmmError::InvalidRoute
+        );
+
+        // A native input is wrapped and a native output unwrapped, as on a
+        // direct swap. The output's balance is read before anything is wrapped,
+        // as a route may start and end in the same account.
+        self.create_native_input(user_in)?;
+        let input = Account::<TokenAccount>::try_from(user_in)?;
+        let user_out = &hops[hops.len() - ACCOUNTS_PER_HOP + 3];
+        let output = Account::<TokenAccount>::try_from(user_out)?;
+
+        wrap_sol(
+            input.mint,
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.user.to_account_info(),
+            user_in.clone(),
+            amount_in,
+        )?;
+
+        let mut user_in = user_in;
+        let mut amount = amount_in;
+
+        for hop in hops.chunks(ACCOUNTS_PER_HOP) {
+            amount = self.swap_hop(user_in, hop, amount)?;
+            user_in = &hop[3];
+        }
+
+        require!(amount >= min_amount_out, AmmError::SlippageExceeded);
+
+        if input.key() != output.key() {
+            unwrap_sol(
+                input.mint,
+                self.token_program.to_account_info(),
+                input.to_account_info(),
+                self.user.to_account_info(),
+                input.amount,
+            )?;
+        }
+
+        // Routes may pay out to someone else's account, which stays as it is.
+        if output.owner != self.user.key() {
+            return Ok(());
+        }
+
+        unwrap_sol(
+            output.mint,
+            self.token_program.to_account_info(),
+            user_out.clone(),
+            self.user.to_account_info(),
+            output.amount,
+        )
+    }
+
+    // A wallet spending plain SOL may not have a wSOL account yet. A direct
+    // swap creates it with `init_if_needed`; a route, whose token accounts
+    // are all remaining accounts, creates it here.
+    fn create_native_input(&self, user_in: &'info AccountInfo<'info>) -> Result<()> {
+        if !user_in.data_is_empty()
+            || user_in.key() != get_associated_token_address(&self.user.key(), &native_mint::ID)
+        {
+            return Ok(());
+        }
+
+        let accounts = Create {
+            payer: self.user.to_account_info(),
+            associated_token: user_in.clone(),
+            authority: self.user.to_account_info(),
+            mint: self.native_mint.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+        create(CpiContext::new(self.associated_token_program.to_account_info(), accounts))
+    }
+
+    // Swaps `amount` out of `user_in` through a single pool and returns the
+    // amount paid out to the hop's output account.
+    fn swap_hop(
+        &self,
+        user_in: &'info AccountInfo<'info>,
+        hop: &'info [AccountInfo<'info>],
+        amount: u64,
+    ) -> Result<u64> {
+        let mut config = Account::<Config>::try_from(&hop[0])?;
+        let vault_x = Account::<TokenAccount>::try_from(&hop[1])?;
+        let vault_y = Account::<TokenAccount>::try_from(&hop[2])?;
+        let user_out = &hop[3];
+
+        require!(!config.is_locked(), AmmError::PoolLocked);
+        require!(!config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check_unconstrained(&config, &self.user.key(), &hop[4])?;
+        // Dynamic-fee pools record every swap's price move.
+        require!(!config.dynamic_fee || hop[0].is_writable, AmmError::InvalidRoute);
+        require_keys_eq!(
+            vault_x.key(),
+            get_associated_token_address(&config.key(), &config.mint_x),
+            AmmError::InvalidToken
+        );
+        require_keys_eq!(
+            vault_y.key(),
+            get_associated_token_address(&config.key(), &config.mint_y),
+            AmmError::InvalidToken
+        );
+
+        let input_mint = Account::<TokenAccount>::try_from(user_in)?.mint;
+        let is_x = match input_mint {
+            mint if mint == config.mint_x => true,
+            mint if mint == config.mint_y => false,
+            _ => return err!(AmmError::InvalidToken),
+        };
+
+        let now = Clock::get()?.unix_timestamp;
+        let res = quote_swap(&config, now, vault_x.amount, vault_y.amount, is_x, amount, 0)?;
+
+        require!(res.amount_in != 0, AmmError::InvalidAmount);
+        require!(res.amount_out != 0, AmmError::InvalidAmount);
+
+        // The pool's invariant may never shrink across a hop.
+        let (x_after, y_after) = match is_x {
+            true => (
+                vault_x.amount.checked_add(res.amount_in).ok_or(AmmError::Overflow)?,
+                vault_y.amount.checked_sub(res.amount_out).ok_or(AmmError::Underflow)?,
+            ),
+            false => (
+                vault_x.amount.checked_sub(res.amount_out).ok_or(AmmError::Underflow)?,
+                vault_y.amount.checked_add(res.amount_in).ok_or(AmmError::Overflow)?,
+            ),
+        };
+        require!(
+            invariant_holds(&config, now, (vault_x.amount, vault_y.amount), (x_after, y_after))?,
+            AmmError::CurveError
+        );
+
+        let (vault_in, vault_out) = match is_x {
+            true => (&hop[1], &hop[2]),
+            false => (&hop[2], &hop[1]),
+        };
+
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            user_in.clone(),
+            vault_in.clone(),
+            self.user.to_account_info(),
+            res.amount_in,
+        )?;
+
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &config,
+            config.to_account_info(),
+            vault_out.clone(),
+            user_out.clone(),
+            res.amount_out,
+        )?;
+
+        if config.dynamic_fee {
+            config.record_swap(now, (vault_x.amount, vault_y.amount), (x_after, y_after));
+            config.exit(&crate::ID)?;
+        }
+
+        emit!(Swapped {
+            config: config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            reserve_x: x_after,
+            reserve_y: y_after,
+        });
+
+        Ok(res.amount_out)
+    }
+}
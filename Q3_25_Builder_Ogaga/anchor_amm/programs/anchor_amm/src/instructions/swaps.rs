@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount, Transfer, transfer}};
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+use amm_quote::{stable_swap, SwapQuote};
 
-use crate::{error::AmmError, state::Config};
+use crate::{error::AmmError, events::Swapped, state::{AllowListEntry, Config, CurveType}};
+
+use super::token_utils::{transfer_from_vault, transfer_to_vault, unwrap_sol, wrap_sol};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -43,12 +45,20 @@ pub struct Swap<'info> {
     )]
     pub vault_y: Account<'info, TokenAccount>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), config.fee.to_le_bytes().as_ref()],
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to this pool and user.
+    #[account(
+        seeds = [b"allow", config.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allow_list_entry: Option<UncheckedAccount<'info>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -56,56 +66,74 @@ pub struct Swap<'info> {
 
 impl<'info> Swap<'info> {
     pub fn swap(&mut self, is_x: bool, amount: u64, min: u64) -> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        require!(!self.config.sunset, AmmError::PoolSunset);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
         require!(amount > 0, AmmError::InvalidAmount);
 
-        let mut curve = ConstantProduct::init(
+        let now = Clock::get()?.unix_timestamp;
+        let res = quote_swap(
+            &self.config,
+            now,
             self.vault_x.amount,
-             self.vault_y.amount, 
-             self.vault_x.amount, 
-             self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
+            self.vault_y.amount,
+            is_x,
+            amount,
+            min,
+        )?;
 
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
-        };
-
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
-
-        require!(res.deposit != 0, AmmError::InvalidAmount);
-        require!(res.withdraw != 0, AmmError::InvalidAmount);
+        require!(res.amount_in != 0, AmmError::InvalidAmount);
+        require!(res.amount_out != 0, AmmError::InvalidAmount);
 
         // deposit tokens
-        self.deposit_tokens(is_x, res.deposit)?;
+        self.deposit_tokens(is_x, res.amount_in)?;
         // withdraw tokens
-        self.withdraw_tokens(is_x, res.withdraw)?;
+        self.withdraw_tokens(is_x, res.amount_out)?;
         // transfer fee
 
-        Ok(())
-    }
-
-    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
-        let (from, to) = match is_x {
-            true => (self.user_x.to_account_info() , self.vault_x.to_account_info()),
-            false => (self.user_y.to_account_info(), self.vault_y.to_account_info()),
+        let (reserve_x, reserve_y) = match is_x {
+            true => (self.vault_x.amount + res.amount_in, self.vault_y.amount - res.amount_out),
+            false => (self.vault_x.amount - res.amount_out, self.vault_y.amount + res.amount_in),
         };
+        let before = (self.vault_x.amount, self.vault_y.amount);
+        self.config.record_swap(now, before, (reserve_x, reserve_y));
 
-        let cpi_program = self.token_program.to_account_info();
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            reserve_x,
+            reserve_y,
+        });
 
-        let accounts = Transfer {
-            from: from.to_account_info(),
-            to: to.to_account_info(),
-            authority: self.user.to_account_info(),
-        };
+        self.unwrap_native()
+    }
 
-        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (mint, from, to) = match is_x {
+            true => (self.mint_x.key(), self.user_x.to_account_info() , self.vault_x.to_account_info()),
+            false => (self.mint_y.key(), self.user_y.to_account_info(), self.vault_y.to_account_info()),
+        };
 
-        transfer(cpi_ctx, amount)?;
+        wrap_sol(
+            mint,
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.user.to_account_info(),
+            from.clone(),
+            amount,
+        )?;
 
-        Ok(())
+        transfer_to_vault(
+            self.token_program.to_account_info(),
+            from,
+            to,
+            self.user.to_account_info(),
+            amount,
+        )
     }
 
     pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
@@ -114,25 +142,147 @@ impl<'info> Swap<'info> {
             false => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
         };
 
-        let cpi_program = self.token_program.to_account_info();
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.config,
+            self.config.to_account_info(),
+            from,
+            to,
+            amount,
+        )
+    }
+
+    fn unwrap_native(&self) -> Result<()> {
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.user_x.to_account_info(),
+            self.user.to_account_info(),
+            self.user_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.user_y.to_account_info(),
+            self.user.to_account_info(),
+            self.user_y.amount,
+        )
+    }
+}
 
-        let accounts = Transfer {
-            from: from.to_account_info(),
-            to: to.to_account_info(),
-            authority: self.config.to_account_info(),
-        };
+// Prices a swap of `amount` of X (or Y) into a pool holding `reserve_x` and
+// `reserve_y` at time `now`, using whichever curve the pool was initialized
+// with. Clients get the same numbers from `amm_quote::quote_swap`.
+pub fn quote_swap(config: &Config, now: i64, reserve_x: u64, reserve_y: u64, is_x: bool, amount: u64, min: u64) -> Result<SwapQuote> {
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),
+        false => (reserve_y, reserve_x),
+    };
+    require!(reserve_in != 0 && reserve_out != 0, AmmError::ZeroBalance);
+
+    let curve = config.quote_curve(now);
+    let res = amm_quote::quote_swap(curve, reserve_in, reserve_out, amount, config.swap_fee(now))
+        .ok_or(AmmError::CurveError)?;
+    require!(res.amount_out >= min, AmmError::SlippageExceeded);
+
+    Ok(res)
+}
 
-        let seeds = &[
-            &b"config"[..],
-            &self.config.seed.to_le_bytes(),
-            &[self.config.config_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
+// Whether moving the reserves from `before` to `after` keeps the pool's curve
+// invariant (x * y for constant product, D for StableSwap) from decreasing.
+// D is only resolved to within one unit, so StableSwap gets that much slack.
+pub fn invariant_holds(config: &Config, now: i64, before: (u64, u64), after: (u64, u64)) -> Result<bool> {
+    match config.curve {
+        CurveType::ConstantProduct => Ok(
+            (after.0 as u128) * (after.1 as u128) >= (before.0 as u128) * (before.1 as u128)
+        ),
+        CurveType::StableSwap => {
+            let amp = config.amp(now);
+            let d_before = stable_swap::compute_d(amp, before.0, before.1).ok_or(AmmError::CurveError)?;
+            let d_after = stable_swap::compute_d(amp, after.0, after.1).ok_or(AmmError::CurveError)?;
+            Ok(d_after + 1 >= d_before)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amm_quote::Curve;
+    use proptest::prelude::*;
+
+    fn config(curve: CurveType, fee: u16, initial_amp: u64, target_amp: u64) -> Config {
+        Config {
+            authority: None,
+            mint_x: Pubkey::new_unique(),
+            mint_y: Pubkey::new_unique(),
+            fee,
+            locked: false,
+            config_bump: 255,
+            lp_bump: 255,
+            curve,
+            initial_amp,
+            target_amp,
+            ramp_start_ts: 1_000,
+            ramp_stop_ts: 2_000,
+            allow_list: false,
+            dynamic_fee: false,
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            last_swap_ts: 0,
+            sunset: false,
+            closed: false,
+            flash_loan_active: false,
+        }
+    }
 
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+    proptest! {
+        #[test]
+        fn on_chain_swaps_match_the_client_quote(
+            stable in any::<bool>(),
+            initial_amp in 1u64..=10_000,
+            target_amp in 1u64..=10_000,
+            now in 0i64..3_000,
+            fee in prop::sample::select(vec![1u16, 5, 30, 100]),
+            reserve_x in 1_000u64..=u64::MAX / 8,
+            reserve_y in 1_000u64..=u64::MAX / 8,
+            is_x in any::<bool>(),
+            amount in 1u64..=u32::MAX as u64,
+        ) {
+            let curve_type = match stable {
+                true => CurveType::StableSwap,
+                false => CurveType::ConstantProduct,
+            };
+            let config = config(curve_type, fee, initial_amp, target_amp);
 
-        transfer(cpi_ctx, amount)?;
+            let (reserve_in, reserve_out) = match is_x {
+                true => (reserve_x, reserve_y),
+                false => (reserve_y, reserve_x),
+            };
+            let curve = match stable {
+                true => Curve::StableSwap {
+                    amp: stable_swap::current_amp(initial_amp, target_amp, 1_000, 2_000, now),
+                },
+                false => Curve::ConstantProduct,
+            };
 
-        Ok(())
+            let Some(expected) = amm_quote::quote_swap(curve, reserve_in, reserve_out, amount, fee) else {
+                prop_assert!(quote_swap(&config, now, reserve_x, reserve_y, is_x, amount, 0).is_err());
+                return Ok(());
+            };
+
+            let res = quote_swap(&config, now, reserve_x, reserve_y, is_x, amount, 0).unwrap();
+            prop_assert_eq!(res, expected);
+
+            // Whatever the program quotes has to pass its own invariant check.
+            let after = match is_x {
+                true => (reserve_x + res.amount_in, reserve_y - res.amount_out),
+                false => (reserve_x - res.amount_out, reserve_y + res.amount_in),
+            };
+            prop_assert!(invariant_holds(&config, now, (reserve_x, reserve_y), after).unwrap());
+
+            prop_assert!(quote_swap(&config, now, reserve_x, reserve_y, is_x, amount, res.amount_out + 1).is_err());
+        }
     }
-}
\ No newline at end of file
+}
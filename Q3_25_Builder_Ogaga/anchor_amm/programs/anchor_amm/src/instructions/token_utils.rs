@@ -0,0 +1,182 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{close_account, spl_token::native_mint, sync_native, CloseAccount, SyncNative, TokenAccount, Transfer, transfer};
+
+use crate::state::{ClPool, Config, Farm};
+
+// Token movements shared by the pool, CL pool and farm instructions: vault
+// transfers signed by whichever PDA owns the vault, and wrapping native SOL.
+
+// Moves `amount` from a user-owned token account into a pool vault.
+pub fn transfer_to_vault<'info>(
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: user,
+    };
+
+    let cpi_ctx = CpiContext::new(token_program, accounts);
+
+    transfer(cpi_ctx, amount)?;
+
+    Ok(())
+}
+
+// Calls `f` with the signer seeds of the pool's config PDA, which owns the
+// pool's vaults and LP mint.
+pub fn config_signer_seeds<R>(config: &Config, f: impl FnOnce(&[&[&[u8]]]) -> R) -> R {
+    let fee = config.fee.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"config",
+        config.mint_x.as_ref(),
+        config.mint_y.as_ref(),
+        &fee,
+        &[config.config_bump],
+    ];
+
+    f(&[seeds])
+}
+
+// Moves `amount` out of a pool vault, signing with the pool's config PDA.
+pub fn transfer_from_vault<'info>(
+    token_program: AccountInfo<'info>,
+    config: &Config,
+    config_info: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: config_info,
+    };
+
+    config_signer_seeds(config, |signer_seeds| {
+        transfer(CpiContext::new_with_signer(token_program, accounts, signer_seeds), amount)
+    })
+}
+
+// Moves `amount` out of a concentrated liquidity pool vault, signing with the
+// pool PDA.
+pub fn transfer_from_cl_vault<'info>(
+    token_program: AccountInfo<'info>,
+    pool: &ClPool,
+    pool_info: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: pool_info,
+    };
+
+    let seeds = &[
+        &b"cl_pool"[..],
+        pool.mint_x.as_ref(),
+        pool.mint_y.as_ref(),
+        &pool.fee.to_le_bytes(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    transfer(cpi_ctx, amount)?;
+
+    Ok(())
+}
+
+// Moves `amount` out of a farm vault, signing with the farm PDA.
+pub fn transfer_from_farm_vault<'info>(
+    token_program: AccountInfo<'info>,
+    farm: &Farm,
+    farm_info: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: farm_info,
+    };
+
+    let seeds = &[
+        &b"farm"[..],
+        farm.config.as_ref(),
+        farm.reward_mint.as_ref(),
+        &[farm.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    transfer(cpi_ctx, amount)?;
+
+    Ok(())
+}
+
+// When `mint` is wSOL, makes sure the user's wSOL account holds `amount` so
+// plain SOL can be spent like any other token. wSOL already in the account is
+// spent first and only the shortfall is wrapped from the wallet. Does nothing
+// for other mints.
+pub fn wrap_sol<'info>(
+    mint: Pubkey,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    wsol_account: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if mint != native_mint::ID || amount == 0 {
+        return Ok(());
+    }
+
+    let held = TokenAccount::try_deserialize(&mut &wsol_account.try_borrow_data()?[..])?.amount;
+    let shortfall = amount.saturating_sub(held);
+    if shortfall == 0 {
+        return Ok(());
+    }
+
+    let accounts = system_program::Transfer {
+        from: user,
+        to: wsol_account.clone(),
+    };
+    system_program::transfer(CpiContext::new(system_program, accounts), shortfall)?;
+
+    let accounts = SyncNative {
+        account: wsol_account,
+    };
+    sync_native(CpiContext::new(token_program, accounts))
+}
+
+// When `mint` is wSOL, closes the user's wSOL account so its whole balance
+// and rent come back to the wallet as SOL. `held` is what the account held
+// before the instruction ran: a wallet that already kept wSOL keeps it, and
+// the account is left open. Does nothing for other mints.
+pub fn unwrap_sol<'info>(
+    mint: Pubkey,
+    token_program: AccountInfo<'info>,
+    wsol_account: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    held: u64,
+) -> Result<()> {
+    if mint != native_mint::ID || held != 0 {
+        return Ok(());
+    }
+
+    let accounts = CloseAccount {
+        account: wsol_account,
+        destination: user.clone(),
+        authority: user,
+    };
+    close_account(CpiContext::new(token_program, accounts))
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Burn, Mint, Token, TokenAccount, Transfer, burn}};
-use constant_product_curve::ConstantProduct;
+use anchor_spl::{associated_token::AssociatedToken, token::{Burn, Mint, Token, TokenAccount, burn}};
 
-use crate::{error::AmmError, state::Config};
+use crate::{error::AmmError, events::LiquidityRemoved, state::{AllowListEntry, Config}};
+
+use super::token_utils::{transfer_from_vault, unwrap_sol};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -13,7 +14,7 @@ pub struct Withdraw<'info> {
     #[account(
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), config.fee.to_le_bytes().as_ref()],
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
@@ -55,6 +56,13 @@ pub struct Withdraw<'info> {
         associated_token::authority = user,
     )]
     pub user_lp: Account<'info, TokenAccount>,
+    /// CHECK: Only looked at on allow-listed pools, and then only for
+    /// whether the entry exists. The seeds tie it to this pool and user.
+    #[account(
+        seeds = [b"allow", config.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub allow_list_entry: Option<UncheckedAccount<'info>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -67,26 +75,51 @@ impl<'info> Withdraw<'info> {
         min_x: u64,  // Minimum amount of token X that the user wants to receive
         min_y: u64,  // Minimum amount of token Y that the user wants to receive
     ) -> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.is_locked(), AmmError::PoolLocked);
+        AllowListEntry::check(&self.config, self.allow_list_entry.as_deref())?;
         require!(amount != 0, AmmError::InvalidAmount);
         require!(min_x != 0 || min_y != 0, AmmError::InvalidAmount);
 
-        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-            self.vault_x.amount, 
-            self.vault_y.amount, 
-            self.mint_lp.supply, 
-            amount, 
-        6,
+        let (x, y) = amm_quote::withdraw_amounts(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            amount,
         )
-        .map_err(AmmError::from)?;
+        .ok_or(AmmError::InsufficientBalance)?;
 
-        require!(min_x <= amounts.x && min_y <= amounts.y, AmmError::SlippageExceeded);
+        require!(min_x <= x && min_y <= y, AmmError::SlippageExceeded);
 
-        self.withdraw_tokens(true, amounts.x)?;
-        self.withdraw_tokens(false, amounts.y)?;
+        self.withdraw_tokens(true, x)?;
+        self.withdraw_tokens(false, y)?;
         self.burn_lp_tokens(amount)?;
-        
-        Ok(())
+
+        emit!(LiquidityRemoved {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_burned: amount,
+            reserve_x: self.vault_x.amount - x,
+            reserve_y: self.vault_y.amount - y,
+            lp_supply: self.mint_lp.supply - amount,
+        });
+
+        // A native side is paid out as SOL.
+        unwrap_sol(
+            self.mint_x.key(),
+            self.token_program.to_account_info(),
+            self.user_x.to_account_info(),
+            self.user.to_account_info(),
+            self.user_x.amount,
+        )?;
+        unwrap_sol(
+            self.mint_y.key(),
+            self.token_program.to_account_info(),
+            self.user_y.to_account_info(),
+            self.user.to_account_info(),
+            self.user_y.amount,
+        )
     }
 
     pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
@@ -95,26 +128,14 @@ impl<'info> Withdraw<'info> {
             false => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
         };
 
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = Transfer {
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            &self.config,
+            self.config.to_account_info(),
             from,
             to,
-            authority: self.config.to_account_info(),
-        };
-
-        let seeds = &[
-            &b"config"[..],
-            &self.config.seed.to_le_bytes(),
-            &[self.config.config_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-        transfer(cpi_ctx, amount)?;
-        
-        Ok(())
+            amount,
+        )
     }
 
     pub fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
@@ -1,11 +1,15 @@
 #![allow(unexpected_cfgs)]
 use anchor_lang::prelude::*;
 
+mod constants;
 mod error;
-mod state;
+pub mod events;
+mod cl_math;
+pub mod state;
 mod instructions;
 
 use instructions::*;
+use state::CurveType;
 
 declare_id!("3MPLHrt29wtqhqVeFXPjiPPsYoSRyWx3kbVenztZBx1n");
 
@@ -13,14 +17,102 @@ declare_id!("3MPLHrt29wtqhqVeFXPjiPPsYoSRyWx3kbVenztZBx1n");
 pub mod anchor_amm {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
-        ctx.accounts.init(seed, fee, authority, ctx.bumps)
+    pub fn initialize(ctx: Context<Initialize>, fee: u16, authority: Option<Pubkey>, curve: CurveType, amp: u64, allow_list: bool) -> Result<()> {
+        ctx.accounts.init(fee, authority, curve, amp, allow_list, ctx.bumps)
+    }
+
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_stop_ts: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, ramp_stop_ts)
+    }
+
+    pub fn set_dynamic_fee(ctx: Context<SetDynamicFee>, enabled: bool, min_fee: u16, max_fee: u16) -> Result<()> {
+        ctx.accounts.set_dynamic_fee(enabled, min_fee, max_fee)
+    }
+
+    pub fn add_to_allow_list(ctx: Context<AddToAllowList>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.add_to_allow_list(wallet, ctx.bumps)
+    }
+
+    pub fn remove_from_allow_list(ctx: Context<RemoveFromAllowList>) -> Result<()> {
+        ctx.accounts.remove_from_allow_list()
+    }
+
+    pub fn initialize_farm(ctx: Context<InitializeFarm>, reward_per_second: u64) -> Result<()> {
+        ctx.accounts.init(reward_per_second, ctx.bumps)
+    }
+
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64, reward_per_second: u64) -> Result<()> {
+        ctx.accounts.fund_farm(amount, reward_per_second)
+    }
+
+    pub fn stake_lp(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_lp(amount, ctx.bumps)
+    }
+
+    pub fn unstake_lp(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_lp(amount, ctx.bumps)
+    }
+
+    pub fn harvest(ctx: Context<ManageStake>) -> Result<()> {
+        ctx.accounts.harvest(ctx.bumps)
+    }
+
+    pub fn place_order(ctx: Context<PlaceOrder>, order_id: u64, amount_in: u64, min_amount_out: u64, tip: u64, expires_at: i64) -> Result<()> {
+        ctx.accounts.place_order(order_id, amount_in, min_amount_out, tip, expires_at, ctx.bumps)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        ctx.accounts.cancel_order()
+    }
+
+    pub fn fill_orders<'info>(ctx: Context<'_, '_, 'info, 'info, FillOrders<'info>>) -> Result<()> {
+        ctx.accounts.fill_orders(ctx.remaining_accounts)
+    }
+
+    pub fn sunset_pool(ctx: Context<SunsetPool>) -> Result<()> {
+        ctx.accounts.sunset_pool()
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        ctx.accounts.close_pool()
+    }
+
+    pub fn migrate_liquidity(ctx: Context<MigrateLiquidity>, amount: u64, min_x: u64, min_y: u64, min_lp_out: u64) -> Result<()> {
+        ctx.accounts.migrate_liquidity(amount, min_x, min_y, min_lp_out)
+    }
+
+    pub fn initialize_cl_pool(ctx: Context<InitializeClPool>, fee: u16, sqrt_price: u128) -> Result<()> {
+        ctx.accounts.init(fee, sqrt_price, ctx.bumps)
+    }
+
+    pub fn initialize_tick_array(ctx: Context<InitializeTickArray>, start_tick_index: i32) -> Result<()> {
+        ctx.accounts.init(start_tick_index)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        ctx.accounts.open_position(tick_lower, tick_upper, ctx.bumps)
+    }
+
+    pub fn increase_liquidity(ctx: Context<ModifyPosition>, liquidity: u128, max_x: u64, max_y: u64) -> Result<()> {
+        ctx.accounts.increase_liquidity(liquidity, max_x, max_y)
+    }
+
+    pub fn decrease_liquidity(ctx: Context<ModifyPosition>, liquidity: u128, min_x: u64, min_y: u64) -> Result<()> {
+        ctx.accounts.decrease_liquidity(liquidity, min_x, min_y)
+    }
+
+    pub fn collect_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+        ctx.accounts.collect_fees()
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
         ctx.accounts.deposit(amount, max_x, max_y)
     }
 
+    pub fn deposit_single(ctx: Context<Deposit>, is_x: bool, amount_in: u64, min_lp_out: u64) -> Result<()> {
+        ctx.accounts.deposit_single(is_x, amount_in, min_lp_out)
+    }
+
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
         ctx.accounts.withdraw(amount, min_x, min_y)
     }
@@ -28,4 +120,16 @@ pub mod anchor_amm {
     pub fn swap(ctx: Context<Swap>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
         ctx.accounts.swap(is_x, amount_in, min_amount_out)
     }
+
+    pub fn route_swap<'info>(ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+        ctx.accounts.route_swap(ctx.remaining_accounts, amount_in, min_amount_out)
+    }
+
+    pub fn flash_loan<'info>(ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>, amount: u64, data: Vec<u8>) -> Result<()> {
+        ctx.accounts.flash_loan(ctx.remaining_accounts, amount, data)
+    }
+
+    pub fn cl_swap<'info>(ctx: Context<'_, '_, 'info, 'info, ClSwap<'info>>, is_x: bool, amount_in: u64, min_amount_out: u64, sqrt_price_limit: u128) -> Result<()> {
+        ctx.accounts.cl_swap(ctx.remaining_accounts, is_x, amount_in, min_amount_out, sqrt_price_limit)
+    }
 }
\ No newline at end of file
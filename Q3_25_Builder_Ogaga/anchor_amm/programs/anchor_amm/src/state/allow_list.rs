@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+use super::Config;
+
+#[account]
+#[derive(InitSpace)]
+pub struct AllowListEntry {
+    pub config: Pubkey, // Pool the wallet is allowed to use
+    pub wallet: Pubkey, // Allowed wallet
+    pub bump: u8, // Bump seed for the entry account
+}
+
+impl AllowListEntry {
+    /// Fails with `NotAllowListed` unless the pool is open to everyone or
+    /// `entry` is an existing allow-list entry. Callers constrain `entry` to
+    /// the PDA for the pool and wallet.
+    pub fn check(config: &Config, entry: Option<&AccountInfo>) -> Result<()> {
+        if !config.allow_list {
+            return Ok(());
+        }
+
+        let listed = entry.is_some_and(|entry| entry.owner == &crate::ID && !entry.data_is_empty());
+        require!(listed, AmmError::NotAllowListed);
+
+        Ok(())
+    }
+
+    /// `check` for an `entry` nothing has tied to the pool and wallet yet,
+    /// such as a route's remaining accounts. On an allow-listed pool the
+    /// entry must be the PDA for `config` and `wallet`.
+    pub fn check_unconstrained(config: &Account<Config>, wallet: &Pubkey, entry: &AccountInfo) -> Result<()> {
+        if !config.allow_list {
+            return Ok(());
+        }
+
+        let (address, _) = Pubkey::find_program_address(
+            &[b"allow", config.key().as_ref(), wallet.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(entry.key(), address, AmmError::NotAllowListed);
+
+        Self::check(config, Some(entry))
+    }
+}
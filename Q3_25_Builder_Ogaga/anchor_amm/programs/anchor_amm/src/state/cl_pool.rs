@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use super::Tick;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ClPool {
+    pub mint_x: Pubkey, // Token X, always the lower of the two mint addresses
+    pub mint_y: Pubkey, // Token Y, always the higher of the two mint addresses
+    pub fee: u16, // Swap fee in basis points
+    pub tick_spacing: u16, // Only every tick_spacing-th tick can bound a position
+    pub sqrt_price: u128, // sqrt(y / x) as Q64.64
+    pub tick_current: i32, // Greatest tick at or below sqrt_price
+    pub liquidity: u128, // Liquidity of the positions in range at tick_current
+    pub fee_growth_global_x: u128, // Fees in X earned per unit of liquidity, Q64.64
+    pub fee_growth_global_y: u128, // Fees in Y earned per unit of liquidity, Q64.64
+    pub bump: u8, // Bump seed for the pool account
+}
+
+impl ClPool {
+    /// Fee growth per unit of liquidity inside `[tick_lower, tick_upper)`.
+    /// Relies on wrapping arithmetic, so only differences between two
+    /// readings are meaningful.
+    pub fn fee_growth_inside(&self, tick_lower: i32, lower: &Tick, tick_upper: i32, upper: &Tick) -> (u128, u128) {
+        let (below_x, below_y) = match self.tick_current >= tick_lower {
+            true => (lower.fee_growth_outside_x, lower.fee_growth_outside_y),
+            false => (
+                self.fee_growth_global_x.wrapping_sub(lower.fee_growth_outside_x),
+                self.fee_growth_global_y.wrapping_sub(lower.fee_growth_outside_y),
+            ),
+        };
+
+        let (above_x, above_y) = match self.tick_current < tick_upper {
+            true => (upper.fee_growth_outside_x, upper.fee_growth_outside_y),
+            false => (
+                self.fee_growth_global_x.wrapping_sub(upper.fee_growth_outside_x),
+                self.fee_growth_global_y.wrapping_sub(upper.fee_growth_outside_y),
+            ),
+        };
+
+        (
+            self.fee_growth_global_x.wrapping_sub(below_x).wrapping_sub(above_x),
+            self.fee_growth_global_y.wrapping_sub(below_y).wrapping_sub(above_y),
+        )
+    }
+}
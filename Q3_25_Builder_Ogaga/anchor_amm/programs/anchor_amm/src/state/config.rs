@@ -1,17 +1,150 @@
 use anchor_lang::prelude::*;
+use amm_quote::{dynamic_fee, stable_swap, Curve};
 
 #[account]
 pub struct Config {
-    pub seed: u64, // Seed to be able to create different pools / configs
     pub authority: Option<Pubkey>, // If we want an authority to lock the config account
-    pub mint_x: Pubkey, // Token X
-    pub mint_y: Pubkey, // Token Y
-    pub fee: u16, // Swap fee in basis points
+    pub mint_x: Pubkey, // Token X, always the lower of the two mint addresses
+    pub mint_y: Pubkey, // Token Y, always the higher of the two mint addresses
+    pub fee: u16, // Swap fee in basis points, doubles as the pool's fee tier
     pub locked: bool, // If the pool is locked
     pub config_bump: u8, // Bump seed for the config account
     pub lp_bump: u8, // Bump seed for the LP token
+    pub curve: CurveType, // Pricing curve chosen at initialize
+    pub initial_amp: u64, // StableSwap amplification at the start of a ramp
+    pub target_amp: u64, // StableSwap amplification at the end of a ramp
+    pub ramp_start_ts: i64, // When the current amplification ramp started
+    pub ramp_stop_ts: i64, // When the current amplification ramp ends
+    pub allow_list: bool, // If only allow-listed wallets may trade and provide liquidity
+    pub dynamic_fee: bool, // If swaps pay a volatility-scaled fee instead of `fee`
+    pub min_fee: u16, // Dynamic fee floor in basis points
+    pub max_fee: u16, // Dynamic fee ceiling in basis points
+    pub volatility: u64, // Recent price movement in basis points, decaying over time
+    pub last_swap_ts: i64, // When volatility was last updated
+    pub sunset: bool, // If the pool is being retired: withdrawals only
+    pub closed: bool, // If close_pool has run; the vaults are gone
+    pub flash_loan_active: bool, // If a flash loan's receiver is running with a vault's tokens
 }
 
 impl Space for Config {
-    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1;
-}
\ No newline at end of file
+    const INIT_SPACE: usize = 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 2 + 2 + 8 + 8 + 1 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+}
+
+impl Config {
+    /// StableSwap amplification in effect at `now`.
+    pub fn amp(&self, now: i64) -> u64 {
+        stable_swap::current_amp(
+            self.initial_amp,
+            self.target_amp,
+            self.ramp_start_ts,
+            self.ramp_stop_ts,
+            now,
+        )
+    }
+
+    /// Whether swaps and liquidity changes are refused: the pool is locked or
+    /// has a flash loan out against its reserves.
+    pub fn is_locked(&self) -> bool {
+        self.locked || self.flash_loan_active
+    }
+
+    /// Swap fee in basis points at `now`: the fee tier, or the
+    /// volatility-scaled fee in dynamic-fee mode.
+    pub fn swap_fee(&self, now: i64) -> u16 {
+        match self.dynamic_fee {
+            true => dynamic_fee::fee(self.min_fee, self.max_fee, self.volatility, now - self.last_swap_ts),
+            false => self.fee,
+        }
+    }
+
+    /// Adds how far a swap moved the reserves from `before` to `after` to the
+    /// volatility accumulator. Only tracked in dynamic-fee mode.
+    pub fn record_swap(&mut self, now: i64, before: (u64, u64), after: (u64, u64)) {
+        if !self.dynamic_fee {
+            return;
+        }
+
+        let moved = dynamic_fee::price_move_bps(self.quote_curve(now), before, after).unwrap_or(0);
+        self.volatility = dynamic_fee::decay(self.volatility, now - self.last_swap_ts).saturating_add(moved);
+        self.last_swap_ts = now;
+    }
+
+    /// The pool's curve as `amm_quote` prices it at `now`.
+    pub fn quote_curve(&self, now: i64) -> Curve {
+        match self.curve {
+            CurveType::ConstantProduct => Curve::ConstantProduct,
+            CurveType::StableSwap => Curve::StableSwap { amp: self.amp(now) },
+        }
+    }
+
+    /// Orders a mint pair the way pools store it: `mint_x < mint_y`.
+    pub fn sort_mints(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, Pubkey) {
+        if mint_a <= mint_b {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        }
+    }
+
+    /// Derives the canonical pool config for a mint pair and fee tier.
+    /// The mints may be passed in either order.
+    pub fn find_pool_address(mint_a: Pubkey, mint_b: Pubkey, fee: u16) -> (Pubkey, u8) {
+        let (mint_x, mint_y) = Self::sort_mints(mint_a, mint_b);
+
+        Pubkey::find_program_address(
+            &[
+                b"config",
+                mint_x.as_ref(),
+                mint_y.as_ref(),
+                fee.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_mints_puts_the_lower_address_first() {
+        let (a, b) = (Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32]));
+
+        assert_eq!(Config::sort_mints(a, b), (a, b));
+        assert_eq!(Config::sort_mints(b, a), (a, b));
+        assert_eq!(Config::sort_mints(a, a), (a, a));
+    }
+
+    #[test]
+    fn find_pool_address_ignores_mint_order() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(Config::find_pool_address(a, b, 30), Config::find_pool_address(b, a, 30));
+    }
+
+    #[test]
+    fn find_pool_address_matches_the_initialize_seeds() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mint_x, mint_y) = Config::sort_mints(a, b);
+
+        let expected = Pubkey::find_program_address(
+            &[b"config", mint_x.as_ref(), mint_y.as_ref(), 30u16.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+        assert_eq!(Config::find_pool_address(b, a, 30), expected);
+    }
+
+    #[test]
+    fn find_pool_address_differs_by_fee_tier() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_ne!(Config::find_pool_address(a, b, 30).0, Config::find_pool_address(a, b, 100).0);
+    }
+}
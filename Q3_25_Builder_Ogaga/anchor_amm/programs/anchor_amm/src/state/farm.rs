@@ -0,0 +1,167 @@
+use amm_quote::U256;
+use anchor_lang::prelude::*;
+
+use crate::{constants::REWARD_PRECISION, error::AmmError};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub config: Pubkey, // Pool whose LP tokens are staked
+    pub reward_mint: Pubkey, // Token paid out as rewards
+    pub reward_per_second: u64, // Rewards emitted per second across all stakers
+    pub reward_per_share: u128, // Rewards emitted per staked LP token, scaled by REWARD_PRECISION
+    pub rewards_unallocated: u64, // Funded rewards not yet emitted
+    pub total_staked: u64, // LP tokens staked in the farm
+    pub last_update_ts: i64, // When reward_per_share was last brought up to date
+    pub bump: u8, // Bump seed for the farm account
+}
+
+impl Farm {
+    /// Emits rewards for the time since the last update. Nothing is emitted
+    /// while nobody is staked, and never more than has been funded.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update_ts {
+            return Ok(());
+        }
+
+        if self.total_staked != 0 {
+            let elapsed = (now - self.last_update_ts) as u128;
+            let emitted = (elapsed * self.reward_per_second as u128).min(self.rewards_unallocated as u128);
+
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(emitted * REWARD_PRECISION / self.total_staked as u128)
+                .ok_or(AmmError::Overflow)?;
+            self.rewards_unallocated -= emitted as u64;
+        }
+
+        self.last_update_ts = now;
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub owner: Pubkey, // Wallet that staked
+    pub farm: Pubkey, // Farm the LP is staked in
+    pub amount: u64, // LP tokens staked
+    pub reward_debt: u128, // amount * reward_per_share at the last settlement, unscaled
+    pub rewards_owed: u64, // Rewards settled and not yet harvested
+    pub bump: u8, // Bump seed for the stake account
+}
+
+impl StakePosition {
+    /// Credits rewards earned since the last settlement and changes the stake
+    /// by `amount_delta` at the farm's current `reward_per_share`.
+    pub fn settle(&mut self, reward_per_share: u128, amount_delta: i64) -> Result<()> {
+        let earned = accrued(self.amount, reward_per_share)?
+            .checked_sub(self.reward_debt)
+            .ok_or(AmmError::Underflow)?;
+        self.rewards_owed = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| self.rewards_owed.checked_add(earned))
+            .ok_or(AmmError::Overflow)?;
+
+        self.amount = self.amount.checked_add_signed(amount_delta).ok_or(AmmError::InsufficientBalance)?;
+        self.reward_debt = accrued(self.amount, reward_per_share)?;
+
+        Ok(())
+    }
+}
+
+// Rewards `amount` staked LP would have earned since the accumulator started.
+fn accrued(amount: u64, reward_per_share: u128) -> Result<u128> {
+    let accrued = U256::from(amount) * U256::from(reward_per_share) / U256::from(REWARD_PRECISION);
+    require!(accrued <= U256::from(u128::MAX), AmmError::Overflow);
+
+    Ok(accrued.as_u128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn farm(reward_per_second: u64, rewards_unallocated: u64) -> Farm {
+        Farm {
+            config: Pubkey::new_unique(),
+            reward_mint: Pubkey::new_unique(),
+            reward_per_second,
+            reward_per_share: 0,
+            rewards_unallocated,
+            total_staked: 0,
+            last_update_ts: 0,
+            bump: 255,
+        }
+    }
+
+    fn stake(farm: &mut Farm, position: &mut StakePosition, now: i64, amount: i64) {
+        farm.update(now).unwrap();
+        position.settle(farm.reward_per_share, amount).unwrap();
+        farm.total_staked = farm.total_staked.checked_add_signed(amount).unwrap();
+    }
+
+    fn position() -> StakePosition {
+        StakePosition {
+            owner: Pubkey::new_unique(),
+            farm: Pubkey::new_unique(),
+            amount: 0,
+            reward_debt: 0,
+            rewards_owed: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn rewards_split_by_stake_and_time() {
+        let mut farm = farm(100, 1_000_000);
+        let (mut alice, mut bob) = (position(), position());
+
+        // Alice alone for 10s, then Bob joins with three times her stake.
+        stake(&mut farm, &mut alice, 0, 1_000);
+        stake(&mut farm, &mut bob, 10, 3_000);
+        stake(&mut farm, &mut alice, 20, 0);
+        stake(&mut farm, &mut bob, 20, 0);
+
+        assert_eq!(alice.rewards_owed, 1_000 + 250);
+        assert_eq!(bob.rewards_owed, 750);
+        assert_eq!(farm.rewards_unallocated, 1_000_000 - 2_000);
+    }
+
+    #[test]
+    fn nothing_accrues_while_the_farm_is_empty() {
+        let mut farm = farm(100, 1_000_000);
+        let mut alice = position();
+
+        stake(&mut farm, &mut alice, 50, 1_000);
+        stake(&mut farm, &mut alice, 60, -1_000);
+        stake(&mut farm, &mut alice, 100, 0);
+
+        assert_eq!(alice.rewards_owed, 1_000);
+        assert_eq!(farm.rewards_unallocated, 1_000_000 - 1_000);
+    }
+
+    #[test]
+    fn emissions_stop_when_funding_runs_out() {
+        let mut farm = farm(100, 500);
+        let mut alice = position();
+
+        stake(&mut farm, &mut alice, 0, 7);
+        stake(&mut farm, &mut alice, 1_000, 0);
+
+        assert!(alice.rewards_owed <= 500);
+        assert!(alice.rewards_owed >= 499);
+        assert_eq!(farm.rewards_unallocated, 0);
+    }
+
+    #[test]
+    fn unstaking_more_than_staked_fails() {
+        let mut farm = farm(100, 1_000);
+        let mut alice = position();
+
+        stake(&mut farm, &mut alice, 0, 10);
+        farm.update(5).unwrap();
+        assert!(alice.settle(farm.reward_per_share, -11).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub owner: Pubkey, // Wallet that placed the order
+    pub config: Pubkey, // Pool the order fills against
+    pub order_id: u64, // Picked by the owner to tell their orders apart
+    pub is_x: bool, // If the order sells X for Y
+    pub amount_in: u64, // Input held in the order's vault
+    pub min_amount_out: u64, // Least output the whole input may be sold for
+    pub tip: u64, // Lamports paid to whoever fills the order
+    pub expires_at: i64, // Order can't be filled from this time on
+    pub bump: u8, // Bump seed for the order account
+}
@@ -1,3 +1,15 @@
 pub mod config;
+pub mod cl_pool;
+pub mod tick_array;
+pub mod position;
+pub mod allow_list;
+pub mod farm;
+pub mod limit_order;
 
-pub use config::*;
\ No newline at end of file
+pub use config::*;
+pub use cl_pool::*;
+pub use tick_array::*;
+pub use position::*;
+pub use allow_list::*;
+pub use farm::*;
+pub use limit_order::*;
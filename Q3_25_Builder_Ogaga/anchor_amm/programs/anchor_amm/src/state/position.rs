@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{cl_math, error::AmmError};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub owner: Pubkey, // LP that owns the position
+    pub pool: Pubkey, // Concentrated liquidity pool the position is in
+    pub tick_lower: i32, // Lower bound of the price range, inclusive
+    pub tick_upper: i32, // Upper bound of the price range, exclusive
+    pub liquidity: u128, // Liquidity provided across the range
+    pub fee_growth_inside_last_x: u128, // Fee growth inside the range at the last update
+    pub fee_growth_inside_last_y: u128,
+    pub tokens_owed_x: u64, // Fees accrued and not yet collected
+    pub tokens_owed_y: u64,
+    pub bump: u8, // Bump seed for the position account
+}
+
+impl Position {
+    /// Credits fees earned since the last update at the position's current
+    /// liquidity.
+    pub fn accrue_fees(&mut self, fee_growth_inside_x: u128, fee_growth_inside_y: u128) -> Result<()> {
+        let owed_x = cl_math::fees_owed(
+            self.liquidity,
+            fee_growth_inside_x.wrapping_sub(self.fee_growth_inside_last_x),
+        )
+        .ok_or(AmmError::Overflow)?;
+        let owed_y = cl_math::fees_owed(
+            self.liquidity,
+            fee_growth_inside_y.wrapping_sub(self.fee_growth_inside_last_y),
+        )
+        .ok_or(AmmError::Overflow)?;
+
+        self.tokens_owed_x = self.tokens_owed_x.checked_add(owed_x).ok_or(AmmError::Overflow)?;
+        self.tokens_owed_y = self.tokens_owed_y.checked_add(owed_y).ok_or(AmmError::Overflow)?;
+        self.fee_growth_inside_last_x = fee_growth_inside_x;
+        self.fee_growth_inside_last_y = fee_growth_inside_y;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+pub const TICK_ARRAY_SIZE: usize = 32;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TickArray {
+    pub pool: Pubkey, // Concentrated liquidity pool the ticks belong to
+    pub start_tick_index: i32, // First tick covered, a multiple of tick_spacing * TICK_ARRAY_SIZE
+    pub ticks: [Tick; TICK_ARRAY_SIZE], // Every tick_spacing-th tick from start_tick_index
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct Tick {
+    pub liquidity_net: i128, // Liquidity added when the price crosses this tick upwards
+    pub liquidity_gross: u128, // Liquidity of all positions bounded by this tick
+    pub fee_growth_outside_x: u128, // Fee growth on the other side of this tick from the current price
+    pub fee_growth_outside_y: u128,
+}
+
+impl TickArray {
+    /// Start index of the tick array that holds `tick_index`.
+    pub fn start_index_for(tick_index: i32, tick_spacing: u16) -> i32 {
+        let ticks_per_array = tick_spacing as i32 * TICK_ARRAY_SIZE as i32;
+        tick_index.div_euclid(ticks_per_array) * ticks_per_array
+    }
+
+    /// Whether `tick_index` falls within this array's range.
+    pub fn covers(&self, tick_index: i32, tick_spacing: u16) -> bool {
+        Self::start_index_for(tick_index, tick_spacing) == self.start_tick_index
+    }
+
+    pub fn tick(&self, tick_index: i32, tick_spacing: u16) -> Result<&Tick> {
+        let offset = self.offset(tick_index, tick_spacing)?;
+        Ok(&self.ticks[offset])
+    }
+
+    pub fn tick_mut(&mut self, tick_index: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.offset(tick_index, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+
+    fn offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        require!(self.covers(tick_index, tick_spacing), AmmError::InvalidTickArray);
+        require!(tick_index % tick_spacing as i32 == 0, AmmError::InvalidTickRange);
+        Ok(((tick_index - self.start_tick_index) / tick_spacing as i32) as usize)
+    }
+}
+
+impl Tick {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+
+    /// Applies a position's liquidity change to one of its bounding ticks
+    /// and returns whether the tick flipped between initialized and not. A
+    /// tick emptied here keeps its fee growth until `clear` is called, so the
+    /// position's fees can still be settled against it.
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        tick_current: i32,
+        liquidity_delta: i128,
+        is_upper: bool,
+        fee_growth_global_x: u128,
+        fee_growth_global_y: u128,
+    ) -> Result<bool> {
+        let was_initialized = self.is_initialized();
+
+        // By convention all growth before a tick is initialized happened
+        // below it.
+        if !self.is_initialized() && tick_index <= tick_current {
+            self.fee_growth_outside_x = fee_growth_global_x;
+            self.fee_growth_outside_y = fee_growth_global_y;
+        }
+
+        self.liquidity_gross = self
+            .liquidity_gross
+            .checked_add_signed(liquidity_delta)
+            .ok_or(AmmError::Overflow)?;
+
+        let net_delta = match is_upper {
+            true => liquidity_delta.checked_neg().ok_or(AmmError::Overflow)?,
+            false => liquidity_delta,
+        };
+        self.liquidity_net = self.liquidity_net.checked_add(net_delta).ok_or(AmmError::Overflow)?;
+
+        Ok(was_initialized != self.is_initialized())
+    }
+
+    /// Resets a tick no position uses any more.
+    pub fn clear(&mut self) {
+        *self = Tick::default();
+    }
+
+    /// Flips fee growth to the other side of the tick as the price crosses
+    /// it and returns the liquidity to add when crossing upwards.
+    pub fn cross(&mut self, fee_growth_global_x: u128, fee_growth_global_y: u128) -> i128 {
+        self.fee_growth_outside_x = fee_growth_global_x.wrapping_sub(self.fee_growth_outside_x);
+        self.fee_growth_outside_y = fee_growth_global_y.wrapping_sub(self.fee_growth_outside_y);
+        self.liquidity_net
+    }
+}
@@ -0,0 +1,52 @@
+//! Flash loan receiver used by the tests. It hands back `repay` of the
+//! borrowed mint, after optionally calling one more instruction while the
+//! loan is out.
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::{prelude::*, solana_program::{instruction::{AccountMeta, Instruction}, program::invoke}};
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+declare_id!("E3vQ82CxD4X8pRUamq7N76jzAQZR8rGfF4qwKw2zWyoJ");
+
+#[program]
+pub mod flash_receiver {
+    use super::*;
+
+    /// Pays `repay` from the borrower's account back into the vault. With a
+    /// non-empty `then`, first calls the program in the first remaining
+    /// account with `then` as its data and the rest as its accounts.
+    pub fn repay<'info>(ctx: Context<'_, '_, 'info, 'info, Repay<'info>>, repay: u64, then: Vec<u8>) -> Result<()> {
+        if let Some((program, accounts)) = ctx.remaining_accounts.split_first() {
+            let ix = Instruction {
+                program_id: program.key(),
+                accounts: accounts
+                    .iter()
+                    .map(|account| AccountMeta {
+                        pubkey: account.key(),
+                        is_signer: account.is_signer,
+                        is_writable: account.is_writable,
+                    })
+                    .collect(),
+                data: then,
+            };
+            invoke(&ix, ctx.remaining_accounts)?;
+        }
+
+        let accounts = Transfer {
+            from: ctx.accounts.borrower_ata.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+        };
+        transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), accounts), repay)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    pub borrower: Signer<'info>,
+    #[account(mut)]
+    pub borrower_ata: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,86 @@
+// Volatility-scaled fees. A pool in dynamic-fee mode keeps an accumulator of
+// recent price movement in basis points; every swap adds how far it moved
+// the price, and the accumulator halves every VOLATILITY_HALF_LIFE seconds.
+// The fee is the pool's minimum plus a share of the accumulator, capped at
+// its maximum.
+
+use crate::{spot_out, Curve, FEE_DENOMINATOR};
+
+/// Seconds for the volatility accumulator to decay by half.
+pub const VOLATILITY_HALF_LIFE: i64 = 300;
+
+/// One basis point of fee is added per this many basis points of recent
+/// price movement.
+pub const VOLATILITY_FEE_DIVISOR: u64 = 10;
+
+// Input size the marginal price is measured with.
+const PRICE_SCALE: u64 = 1_000_000_000_000;
+
+/// `volatility` after `elapsed` seconds of decay: halved once per half-life,
+/// and linearly towards the next halving in between.
+pub fn decay(volatility: u64, elapsed: i64) -> u64 {
+    if elapsed <= 0 {
+        return volatility;
+    }
+
+    let halvings = elapsed / VOLATILITY_HALF_LIFE;
+    if halvings >= 64 {
+        return 0;
+    }
+
+    let halved = volatility >> halvings;
+    let into_period = (elapsed % VOLATILITY_HALF_LIFE) as u128;
+    halved - (halved as u128 * into_period / (2 * VOLATILITY_HALF_LIFE as u128)) as u64
+}
+
+/// Swap fee in basis points for a pool whose accumulator read `volatility`
+/// `elapsed` seconds ago.
+pub fn fee(min_fee: u16, max_fee: u16, volatility: u64, elapsed: i64) -> u16 {
+    let variable = decay(volatility, elapsed) / VOLATILITY_FEE_DIVISOR;
+    (min_fee as u64).saturating_add(variable).min(max_fee as u64) as u16
+}
+
+/// How far a swap moved the pool's marginal price of X in Y, in basis points
+/// of the price before it.
+pub fn price_move_bps(curve: Curve, before: (u64, u64), after: (u64, u64)) -> Option<u64> {
+    let price_before = spot_out(curve, before.0, before.1, PRICE_SCALE)?;
+    let price_after = spot_out(curve, after.0, after.1, PRICE_SCALE)?;
+    if price_before == 0 {
+        return None;
+    }
+
+    let moved = price_before.abs_diff(price_after);
+    Some(u64::try_from(moved.saturating_mul(FEE_DENOMINATOR) / price_before).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volatility_halves_every_half_life() {
+        assert_eq!(decay(1_000, 0), 1_000);
+        assert_eq!(decay(1_000, VOLATILITY_HALF_LIFE), 500);
+        assert_eq!(decay(1_000, 2 * VOLATILITY_HALF_LIFE), 250);
+        assert_eq!(decay(1_000, VOLATILITY_HALF_LIFE / 2), 750);
+        assert_eq!(decay(u64::MAX, 64 * VOLATILITY_HALF_LIFE), 0);
+    }
+
+    #[test]
+    fn fee_stays_within_bounds() {
+        assert_eq!(fee(5, 100, 0, 0), 5);
+        assert_eq!(fee(5, 100, 200, 0), 25);
+        assert_eq!(fee(5, 100, 200, VOLATILITY_HALF_LIFE), 15);
+        assert_eq!(fee(5, 100, u64::MAX, 0), 100);
+    }
+
+    #[test]
+    fn price_move_is_measured_against_the_old_price() {
+        let curve = Curve::ConstantProduct;
+        assert_eq!(price_move_bps(curve, (1_000_000, 1_000_000), (1_000_000, 1_000_000)), Some(0));
+        // Doubling Y doubles the price of X.
+        assert_eq!(price_move_bps(curve, (1_000_000, 1_000_000), (1_000_000, 2_000_000)), Some(10_000));
+        assert_eq!(price_move_bps(curve, (1_000_000, 1_000_000), (2_000_000, 1_000_000)), Some(5_000));
+        assert_eq!(price_move_bps(curve, (0, 1_000_000), (1, 1_000_000)), None);
+    }
+}
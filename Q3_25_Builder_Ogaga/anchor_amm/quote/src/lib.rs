@@ -0,0 +1,328 @@
+//! Pricing for anchor_amm pools.
+//!
+//! Everything the program uses to price swaps, deposits and withdrawals lives
+//! here as pure functions, so clients can quote exactly what the program will
+//! do on-chain. The crate is `no_std` and has no Solana dependencies.
+
+#![no_std]
+
+pub mod dynamic_fee;
+pub mod stable_swap;
+
+pub use u256::U256;
+
+pub const FEE_DENOMINATOR: u128 = 10_000;
+
+/// The pricing curve of a pool, with whatever parameters are in effect when
+/// the quote is taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap { amp: u64 },
+}
+
+/// The result of pricing a swap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Input taken from the trader, fee included.
+    pub amount_in: u64,
+    /// Output paid to the trader.
+    pub amount_out: u64,
+    /// Part of `amount_in` kept by the pool as the fee.
+    pub fee: u64,
+    /// How far the execution price falls short of the pool's marginal price,
+    /// in basis points, ignoring the fee.
+    pub price_impact_bps: u64,
+}
+
+// The macro expansion trips a handful of clippy lints we don't control.
+#[allow(clippy::all)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+/// Output of a constant-product swap of `amount_in`, with `fee` basis points
+/// taken from the input. Rounds down.
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Option<u64> {
+    let fee = fee as u128;
+    if fee > FEE_DENOMINATOR {
+        return None;
+    }
+
+    let amount_in_after_fee = (amount_in as u128)
+        .checked_mul(FEE_DENOMINATOR - fee)?
+        / FEE_DENOMINATOR;
+    let denominator = (reserve_in as u128).checked_add(amount_in_after_fee)?;
+    if denominator == 0 {
+        return None;
+    }
+
+    let out = (reserve_out as u128).checked_mul(amount_in_after_fee)? / denominator;
+    u64::try_from(out).ok()
+}
+
+/// Prices a swap of `amount_in` into a pool holding `reserve_in` and
+/// `reserve_out`, with `fee` basis points taken from the input.
+pub fn quote_swap(curve: Curve, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Option<SwapQuote> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+
+    let (amount_out, fee_amount) = match curve {
+        Curve::ConstantProduct => {
+            let amount_out = swap_amount_out(reserve_in, reserve_out, amount_in, fee)?;
+            (amount_out, amount_in - amount_after_fee(amount_in, fee)?)
+        }
+        Curve::StableSwap { amp } => stable_swap::swap_amount_out(amp, reserve_in, reserve_out, amount_in, fee)?,
+    };
+
+    let ideal_out = spot_out(curve, reserve_in, reserve_out, amount_in - fee_amount)?;
+    let price_impact_bps = match ideal_out {
+        0 => 0,
+        ideal_out => {
+            let shortfall = ideal_out.saturating_sub(amount_out as u128);
+            (shortfall * FEE_DENOMINATOR / ideal_out) as u64
+        }
+    };
+
+    Some(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: fee_amount,
+        price_impact_bps,
+    })
+}
+
+/// What `amount_in` would buy at the pool's marginal price, before fees and
+/// with no price impact.
+pub fn spot_amount_out(curve: Curve, reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u64> {
+    u64::try_from(spot_out(curve, reserve_in, reserve_out, amount_in)?).ok()
+}
+
+// `spot_amount_out` without narrowing, since a large trade can be worth more
+// than a u64 at the marginal price.
+fn spot_out(curve: Curve, reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u128> {
+    if reserve_in == 0 {
+        return None;
+    }
+
+    match curve {
+        Curve::ConstantProduct => Some((amount_in as u128) * (reserve_out as u128) / reserve_in as u128),
+        // The StableSwap marginal price has no neat closed form; read it off
+        // a swap small enough to barely move the pool.
+        Curve::StableSwap { amp } => {
+            let probe = (reserve_in / 10_000).max(1);
+            let (probe_out, _) = stable_swap::swap_amount_out(amp, reserve_in, reserve_out, probe, 0)?;
+            // swap_amount_out shaves a unit off to cover rounding.
+            let probe_out = probe_out as u128 + 1;
+            Some((amount_in as u128).checked_mul(probe_out)? / probe as u128)
+        }
+    }
+}
+
+/// Token amounts a depositor must add to mint `lp` from a pool holding
+/// `reserve_x`, `reserve_y` with `supply` LP outstanding. Rounds up in the
+/// pool's favour.
+pub fn deposit_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Option<(u64, u64)> {
+    if supply == 0 {
+        return None;
+    }
+
+    let share = |reserve: u64| {
+        let amount = (reserve as u128).checked_mul(lp as u128)?.div_ceil(supply as u128);
+        u64::try_from(amount).ok()
+    };
+
+    Some((share(reserve_x)?, share(reserve_y)?))
+}
+
+/// Token amounts paid out for burning `lp` from a pool holding `reserve_x`,
+/// `reserve_y` with `supply` LP outstanding. Rounds down in the pool's
+/// favour.
+pub fn withdraw_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Option<(u64, u64)> {
+    if supply == 0 || lp > supply {
+        return None;
+    }
+
+    let share = |reserve: u64| (reserve as u128 * lp as u128 / supply as u128) as u64;
+
+    Some((share(reserve_x), share(reserve_y)))
+}
+
+/// LP minted for the first deposit into an empty pool, before any of it is
+/// locked: `sqrt(x * y)` for constant product, the invariant `D` for
+/// StableSwap.
+pub fn initial_lp(curve: Curve, x: u64, y: u64) -> Option<u64> {
+    match curve {
+        Curve::ConstantProduct => initial_liquidity(x, y),
+        Curve::StableSwap { amp } => stable_swap::compute_d(amp, x, y)
+            .filter(|d| *d <= U256::from(u64::MAX))
+            .map(|d| d.as_u64()),
+    }
+}
+
+/// `zap_swap_amount` priced on `curve` with `fee` basis points.
+pub fn quote_zap(curve: Curve, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Option<u64> {
+    zap_swap_amount(reserve_in, reserve_out, amount_in, |s| {
+        quote_swap(curve, reserve_in, reserve_out, s, fee).map(|q| q.amount_out)
+    })
+}
+
+/// How much of a single-sided deposit of `amount_in` to swap through the pool
+/// so that the remaining input and the swap output match the post-swap
+/// reserve ratio. `quote` prices a swap of `s` input on the pool's curve.
+///
+/// This is the largest `s` for which the leftover input is still at least in
+/// proportion to the output, i.e. `(amount_in - s) / out(s) >= (reserve_in + s) / (reserve_out - out(s))`.
+pub fn zap_swap_amount(reserve_in: u64, reserve_out: u64, amount_in: u64, quote: impl Fn(u64) -> Option<u64>) -> Option<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (0u64, amount_in);
+
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        let out = quote(mid)?;
+        if out >= reserve_out {
+            return None;
+        }
+
+        let remaining_in = (amount_in - mid) as u128 * (reserve_out - out) as u128;
+        let swapped_in = (out as u128).checked_mul(reserve_in as u128 + mid as u128);
+
+        match swapped_in {
+            Some(swapped_in) if remaining_in >= swapped_in => lo = mid,
+            _ => hi = mid - 1,
+        }
+    }
+
+    Some(lo)
+}
+
+/// LP tokens owed for adding `x` and `y` to a pool holding `reserve_x`,
+/// `reserve_y` with `supply` LP outstanding. Rounds down in the pool's favour.
+pub fn lp_for_deposit(reserve_x: u64, reserve_y: u64, supply: u64, x: u64, y: u64) -> Option<u64> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return None;
+    }
+
+    let lp_x = (x as u128).checked_mul(supply as u128)? / reserve_x as u128;
+    let lp_y = (y as u128).checked_mul(supply as u128)? / reserve_y as u128;
+
+    u64::try_from(lp_x.min(lp_y)).ok()
+}
+
+/// Fee owed on a flash loan of `amount` at `fee` basis points. Rounds up so
+/// every non-zero loan from a fee-charging pool pays something.
+pub fn flash_loan_fee(amount: u64, fee: u16) -> Option<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee as u128)?
+        .div_ceil(FEE_DENOMINATOR);
+    u64::try_from(fee).ok()
+}
+
+/// Liquidity minted for the first deposit into an empty pool, `sqrt(x * y)`
+/// rounded down.
+pub fn initial_liquidity(x: u64, y: u64) -> Option<u64> {
+    u64::try_from(isqrt((x as u128) * (y as u128))).ok()
+}
+
+// `amount` less `fee` basis points, rounded down.
+fn amount_after_fee(amount: u64, fee: u16) -> Option<u64> {
+    let fee = fee as u128;
+    if fee > FEE_DENOMINATOR {
+        return None;
+    }
+    Some(((amount as u128) * (FEE_DENOMINATOR - fee) / FEE_DENOMINATOR) as u64)
+}
+
+// Integer square root, rounded down.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_amount_out_takes_fee_from_input() {
+        assert_eq!(swap_amount_out(1_000, 1_000, 1_000, 0), Some(500));
+        assert_eq!(swap_amount_out(1_000_000, 1_000_000, 1_000, 30), Some(996));
+        assert_eq!(swap_amount_out(1_000, 1_000, 1_000, 10_001), None);
+    }
+
+    fn zap(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Option<u64> {
+        zap_swap_amount(reserve_in, reserve_out, amount_in, |s| {
+            swap_amount_out(reserve_in, reserve_out, s, fee)
+        })
+    }
+
+    #[test]
+    fn zap_swaps_a_little_under_half_of_a_small_deposit() {
+        let s = zap(1_000_000_000, 1_000_000_000, 1_000_000, 0).unwrap();
+        assert!(s < 500_000 && s > 499_000, "swapped {}", s);
+    }
+
+    #[test]
+    fn zap_leaves_remainder_in_pool_ratio() {
+        let (reserve_in, reserve_out, amount_in, fee) = (5_000_000_000, 2_000_000_000, 250_000_000, 30);
+
+        let s = zap(reserve_in, reserve_out, amount_in, fee).unwrap();
+        let out = swap_amount_out(reserve_in, reserve_out, s, fee).unwrap();
+
+        let (new_in, new_out) = (reserve_in + s, reserve_out - out);
+        let remaining = amount_in - s;
+
+        // Depositing `remaining` against `out` should leave at most a rounding
+        // unit of either side unused.
+        let needed_in = (out as u128 * new_in as u128 / new_out as u128) as u64;
+        assert!(remaining >= needed_in);
+        assert!(remaining - needed_in <= 3, "left over {}", remaining - needed_in);
+    }
+
+    #[test]
+    fn zap_handles_the_whole_range_of_deposit_sizes() {
+        for amount_in in [1, 10, 1_000, u32::MAX as u64, u64::MAX / 2] {
+            let s = zap(u64::MAX / 4, u64::MAX / 4, amount_in, 30).unwrap();
+            assert!(s <= amount_in);
+        }
+        assert_eq!(zap(0, 1_000, 1_000, 30), None);
+    }
+
+    #[test]
+    fn flash_loan_fee_rounds_up() {
+        assert_eq!(flash_loan_fee(1_000_000, 30), Some(3_000));
+        assert_eq!(flash_loan_fee(1, 30), Some(1));
+        assert_eq!(flash_loan_fee(1_000, 0), Some(0));
+    }
+
+    #[test]
+    fn initial_liquidity_is_the_geometric_mean() {
+        assert_eq!(initial_liquidity(0, 1_000), Some(0));
+        assert_eq!(initial_liquidity(1_000_000, 1_000_000), Some(1_000_000));
+        assert_eq!(initial_liquidity(2, 8), Some(4));
+        assert_eq!(initial_liquidity(10, 10_000), Some(316));
+        assert_eq!(initial_liquidity(u64::MAX, u64::MAX), Some(u64::MAX));
+    }
+
+    #[test]
+    fn lp_for_deposit_uses_the_limiting_side() {
+        assert_eq!(lp_for_deposit(1_000, 2_000, 100, 100, 200), Some(10));
+        assert_eq!(lp_for_deposit(1_000, 2_000, 100, 100, 100), Some(5));
+        assert_eq!(lp_for_deposit(0, 2_000, 100, 100, 100), None);
+    }
+}
@@ -0,0 +1,200 @@
+// Two-coin StableSwap (Curve) invariant:
+//
+//   A·n^n·(x + y) + D = A·D·n^n + D^(n+1) / (n^n·x·y),  n = 2
+//
+// Everything here is pure so it can be tested without a runtime. Intermediate
+// products of D overflow u128, so the iterations run in 256 bits.
+
+use crate::FEE_DENOMINATOR;
+
+// U256 was first defined here; keep the old path working for callers.
+pub use crate::U256;
+
+const N_COINS: u64 = 2;
+const MAX_ITERATIONS: usize = 256;
+
+/// Amplification at `now` while ramping linearly from `initial_amp` at
+/// `ramp_start_ts` to `target_amp` at `ramp_stop_ts`.
+pub fn current_amp(initial_amp: u64, target_amp: u64, ramp_start_ts: i64, ramp_stop_ts: i64, now: i64) -> u64 {
+    if now >= ramp_stop_ts || ramp_stop_ts <= ramp_start_ts {
+        return target_amp;
+    }
+    if now <= ramp_start_ts {
+        return initial_amp;
+    }
+
+    let elapsed = (now - ramp_start_ts) as u128;
+    let duration = (ramp_stop_ts - ramp_start_ts) as u128;
+
+    if target_amp >= initial_amp {
+        initial_amp + ((target_amp - initial_amp) as u128 * elapsed / duration) as u64
+    } else {
+        initial_amp - ((initial_amp - target_amp) as u128 * elapsed / duration) as u64
+    }
+}
+
+/// The invariant `D` for reserves `x` and `y`.
+pub fn compute_d(amp: u64, x: u64, y: u64) -> Option<U256> {
+    let sum = U256::from(x) + U256::from(y);
+    if sum.is_zero() {
+        return Some(U256::zero());
+    }
+    if x == 0 || y == 0 || amp == 0 {
+        return None;
+    }
+
+    let n = U256::from(N_COINS);
+    let ann = U256::from(amp) * n * n;
+    let (x, y) = (U256::from(x), U256::from(y));
+
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // One division rather than Curve's two keeps the truncation error
+        // small enough that lopsided pools still converge.
+        let d_p = d.checked_mul(d)?.checked_mul(d)? / (x * y * n * n);
+
+        let d_prev = d;
+        let numerator = (ann * sum + d_p * n).checked_mul(d)?;
+        let denominator = (ann - 1) * d + (n + 1) * d_p;
+        d = numerator / denominator;
+
+        if abs_diff(d, d_prev) <= U256::one() {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// The reserve of the other coin that keeps the invariant at `d` once this
+/// coin's reserve is `x`.
+pub fn compute_y(amp: u64, x: u64, d: U256) -> Option<U256> {
+    if x == 0 || amp == 0 {
+        return None;
+    }
+
+    let n = U256::from(N_COINS);
+    let ann = U256::from(amp) * n * n;
+    let x = U256::from(x);
+
+    let c = d.checked_mul(d)? / (x * n);
+    let c = c.checked_mul(d)? / (ann * n);
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y.checked_mul(y)? + c;
+        let denominator = (y * U256::from(2u64) + b).checked_sub(d)?;
+        if denominator.is_zero() {
+            return None;
+        }
+        y = numerator / denominator;
+
+        if abs_diff(y, y_prev) <= U256::one() {
+            return Some(y);
+        }
+    }
+
+    None
+}
+
+/// Output of swapping `amount_in` into a StableSwap pool, with `fee` basis
+/// points taken from the input. Returns `(amount_out, fee_amount)`.
+pub fn swap_amount_out(amp: u64, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Option<(u64, u64)> {
+    let fee = fee as u128;
+    if fee > FEE_DENOMINATOR {
+        return None;
+    }
+
+    let amount_in_after_fee = u64::try_from(
+        (amount_in as u128).checked_mul(FEE_DENOMINATOR - fee)? / FEE_DENOMINATOR,
+    )
+    .ok()?;
+    let fee_amount = amount_in - amount_in_after_fee;
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = reserve_in.checked_add(amount_in_after_fee)?;
+    let new_reserve_out = compute_y(amp, new_reserve_in, d)?;
+
+    // Round against the trader by one unit to absorb the iteration's error.
+    let amount_out = U256::from(reserve_out)
+        .checked_sub(new_reserve_out)?
+        .checked_sub(U256::one())
+        .unwrap_or_default();
+
+    Some((amount_out.as_u64(), fee_amount))
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn d_of_a_balanced_pool_is_the_sum_of_reserves() {
+        let d = compute_d(100, 1_000_000_000, 1_000_000_000).unwrap();
+        assert_eq!(d, U256::from(2_000_000_000u64));
+        assert_eq!(compute_d(100, 0, 0), Some(U256::zero()));
+        assert_eq!(compute_d(100, 0, 1_000), None);
+    }
+
+    #[test]
+    fn compute_y_inverts_compute_d() {
+        let (amp, x, y) = (200, 3_000_000_000, 1_000_000_000);
+        let d = compute_d(amp, x, y).unwrap();
+        let y_back = compute_y(amp, x, d).unwrap();
+        assert!(abs_diff(y_back, U256::from(y)) <= U256::from(2u64));
+    }
+
+    #[test]
+    fn stable_swap_beats_constant_product_near_peg() {
+        let (reserve, amount_in) = (1_000_000_000_000, 10_000_000_000);
+
+        let (stable_out, fee) = swap_amount_out(100, reserve, reserve, amount_in, 1).unwrap();
+        let cp_out = crate::swap_amount_out(reserve, reserve, amount_in, 1).unwrap();
+
+        assert_eq!(fee, 1_000_000);
+        assert!(stable_out > cp_out);
+        assert!(stable_out < amount_in);
+        assert!(stable_out > amount_in / 100 * 99, "out {}", stable_out);
+    }
+
+    #[test]
+    fn swap_never_pays_out_the_whole_reserve() {
+        let (out, _) = swap_amount_out(1_000, 1_000_000, 1_000_000, u64::MAX / 4, 0).unwrap();
+        assert!(out < 1_000_000);
+    }
+
+    #[test]
+    fn swaps_never_shrink_d() {
+        let amp = 500;
+        for (x, y, amount_in) in [
+            (1_000_000, 1_000_000, 1),
+            (1_000_000_000, 1_000_000_000, 10_000_000),
+            (5_000_000_000, 100_000_000, 2_000_000_000),
+            (100_000_000, 5_000_000_000, 10_000),
+        ] {
+            let (out, _) = swap_amount_out(amp, x, y, amount_in, 1).unwrap();
+            let d_before = compute_d(amp, x, y).unwrap();
+            let d_after = compute_d(amp, x + amount_in, y - out).unwrap();
+            assert!(d_after + 1 >= d_before, "{} -> {}", d_before, d_after);
+        }
+    }
+
+    #[test]
+    fn amp_ramps_linearly() {
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 500), 100);
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 1_500), 150);
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 2_500), 200);
+        assert_eq!(current_amp(200, 100, 1_000, 2_000, 1_250), 175);
+        assert_eq!(current_amp(0, 100, 0, 0, 0), 100);
+    }
+}
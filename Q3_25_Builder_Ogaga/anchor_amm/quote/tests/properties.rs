@@ -0,0 +1,128 @@
+use amm_quote::{
+    deposit_amounts, lp_for_deposit, quote_swap, quote_zap, stable_swap, withdraw_amounts, Curve,
+    FEE_DENOMINATOR, U256,
+};
+use proptest::prelude::*;
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1_000u64..=u64::MAX / 4
+}
+
+fn fee() -> impl Strategy<Value = u16> {
+    prop::sample::select(vec![0u16, 1, 5, 30, 100])
+}
+
+fn curve() -> impl Strategy<Value = Curve> {
+    prop_oneof![
+        Just(Curve::ConstantProduct),
+        (1u64..=1_000_000).prop_map(|amp| Curve::StableSwap { amp }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn constant_product_swaps_never_shrink_k(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u64::MAX / 4,
+        fee in fee(),
+    ) {
+        let quote = quote_swap(Curve::ConstantProduct, reserve_in, reserve_out, amount_in, fee).unwrap();
+
+        prop_assert!(quote.amount_out < reserve_out);
+        let k_before = U256::from(reserve_in) * U256::from(reserve_out);
+        let k_after = U256::from(reserve_in + amount_in) * U256::from(reserve_out - quote.amount_out);
+        prop_assert!(k_after >= k_before);
+    }
+
+    #[test]
+    fn stable_swaps_never_shrink_d(
+        amp in 1u64..=1_000_000,
+        reserve_in in 1_000u64..=u64::MAX / 16,
+        reserve_out in 1_000u64..=u64::MAX / 16,
+        amount_in in 1u64..=u64::MAX / 16,
+        fee in fee(),
+    ) {
+        let curve = Curve::StableSwap { amp };
+        // Very lopsided pools can leave the solver without a root.
+        let Some(quote) = quote_swap(curve, reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+
+        prop_assert!(quote.amount_out < reserve_out);
+        let d_before = stable_swap::compute_d(amp, reserve_in, reserve_out).unwrap();
+        let d_after = stable_swap::compute_d(amp, reserve_in + amount_in, reserve_out - quote.amount_out).unwrap();
+        prop_assert!(d_after + 1 >= d_before);
+    }
+
+    #[test]
+    fn swap_fee_matches_the_fee_tier(
+        curve in curve(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u32::MAX as u64,
+        fee in fee(),
+    ) {
+        if let Some(quote) = quote_swap(curve, reserve_in, reserve_out, amount_in, fee) {
+            let expected = amount_in as u128 - amount_in as u128 * (FEE_DENOMINATOR - fee as u128) / FEE_DENOMINATOR;
+            prop_assert_eq!(quote.amount_in, amount_in);
+            prop_assert_eq!(quote.fee as u128, expected);
+            prop_assert!(quote.price_impact_bps <= FEE_DENOMINATOR as u64);
+        }
+    }
+
+    #[test]
+    fn bigger_constant_product_trades_have_more_impact(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1_000u64..=u32::MAX as u64,
+    ) {
+        let small = quote_swap(Curve::ConstantProduct, reserve_in, reserve_out, amount_in, 30).unwrap();
+        let large = quote_swap(Curve::ConstantProduct, reserve_in, reserve_out, amount_in * 100, 30).unwrap();
+        prop_assert!(large.price_impact_bps >= small.price_impact_bps);
+    }
+
+    #[test]
+    fn deposits_mint_at_least_the_quoted_lp(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        supply in 1_000u64..=u64::MAX / 4,
+        lp in 1u64..=u32::MAX as u64,
+    ) {
+        let Some((x, y)) = deposit_amounts(reserve_x, reserve_y, supply, lp) else {
+            return Ok(());
+        };
+
+        let minted = lp_for_deposit(reserve_x, reserve_y, supply, x, y).unwrap();
+        prop_assert!(minted >= lp);
+    }
+
+    #[test]
+    fn depositing_then_withdrawing_never_profits(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        supply in 1_000u64..=u64::MAX / 4,
+        lp in 1u64..=u32::MAX as u64,
+    ) {
+        let Some((x, y)) = deposit_amounts(reserve_x, reserve_y, supply, lp) else {
+            return Ok(());
+        };
+
+        let (out_x, out_y) = withdraw_amounts(reserve_x + x, reserve_y + y, supply + lp, lp).unwrap();
+        prop_assert!(out_x <= x);
+        prop_assert!(out_y <= y);
+    }
+
+    #[test]
+    fn zap_never_swaps_more_than_the_deposit(
+        curve in curve(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u32::MAX as u64,
+        fee in fee(),
+    ) {
+        if let Some(s) = quote_zap(curve, reserve_in, reserve_out, amount_in, fee) {
+            prop_assert!(s <= amount_in);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("allow-listed pools", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const fee = 30;
+
+  const [listed, outsider] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  );
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const entryFor = (wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("allow"), config.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(mint.publicKey, owner.publicKey);
+
+  const accountsFor = (user: Keypair) => ({
+    user: user.publicKey,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    config,
+    allowListEntry: entryFor(user.publicKey),
+  });
+
+  // Remaining accounts for a single-hop route from X into Y through the
+  // pool, with the allow-list entry `entry`.
+  const route = (user: Keypair, entry: PublicKey) =>
+    [
+      ata(mintX, user),
+      config,
+      getAssociatedTokenAddressSync(mintX.publicKey, config, true),
+      getAssociatedTokenAddressSync(mintY.publicKey, config, true),
+      ata(mintY, user),
+      entry,
+    ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  it("Airdrop and create mints", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[listed, outsider].map((account) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: account.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[listed, outsider].flatMap((user) =>
+        [mintX, mintY].flatMap((mint) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, user),
+            user.publicKey,
+            mint.publicKey
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, user),
+            provider.publicKey,
+            1e9
+          ),
+        ])
+      ),
+    ];
+
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+  });
+
+  it("Initializes an allow-listed pool", async () => {
+    await program.methods
+      .initialize(fee, provider.publicKey, { constantProduct: {} }, new BN(0), true)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .rpc();
+
+    const state = await program.account.config.fetch(config);
+    expect(state.allowList).to.equal(true);
+  });
+
+  it("Adds a wallet to the allow list", async () => {
+    await program.methods
+      .addToAllowList(listed.publicKey)
+      .accountsPartial({
+        authority: provider.publicKey,
+        config,
+        entry: entryFor(listed.publicKey),
+      })
+      .rpc();
+  });
+
+  it("Rejects allow-list changes from anyone but the authority", async () => {
+    await expectError(
+      program.methods
+        .addToAllowList(outsider.publicKey)
+        .accountsPartial({
+          authority: outsider.publicKey,
+          config,
+          entry: entryFor(outsider.publicKey),
+        })
+        .signers([outsider])
+        .rpc(),
+      "InvalidAuthority"
+    );
+  });
+
+  it("Lets a listed wallet deposit and swap", async () => {
+    await program.methods
+      .deposit(new BN(1e6), new BN(1e8), new BN(1e8))
+      .accountsPartial(accountsFor(listed))
+      .signers([listed])
+      .rpc();
+
+    await program.methods
+      .swap(true, new BN(1e6), new BN(1))
+      .accountsPartial(accountsFor(listed))
+      .signers([listed])
+      .rpc();
+  });
+
+  it("Rejects deposits and swaps from a wallet that isn't listed", async () => {
+    await expectError(
+      program.methods
+        .deposit(new BN(1e6), new BN(1e8), new BN(1e8))
+        .accountsPartial(accountsFor(outsider))
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+
+    await expectError(
+      program.methods
+        .swap(true, new BN(1e6), new BN(1))
+        .accountsPartial(accountsFor(outsider))
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+
+    // Leaving the entry out altogether fails the same way.
+    await expectError(
+      program.methods
+        .swap(true, new BN(1e6), new BN(1))
+        .accountsPartial({ ...accountsFor(outsider), allowListEntry: null })
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+  });
+
+  it("Checks every hop of a route against the allow list", async () => {
+    await program.methods
+      .routeSwap(new BN(1e6), new BN(1))
+      .accounts({ user: listed.publicKey })
+      .remainingAccounts(route(listed, entryFor(listed.publicKey)))
+      .signers([listed])
+      .rpc();
+
+    await expectError(
+      program.methods
+        .routeSwap(new BN(1e6), new BN(1))
+        .accounts({ user: outsider.publicKey })
+        .remainingAccounts(route(outsider, entryFor(outsider.publicKey)))
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+
+    // Someone else's entry doesn't get a wallet in either.
+    await expectError(
+      program.methods
+        .routeSwap(new BN(1e6), new BN(1))
+        .accounts({ user: outsider.publicKey })
+        .remainingAccounts(route(outsider, entryFor(listed.publicKey)))
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+  });
+
+  it("Only lends to listed wallets", async () => {
+    await expectError(
+      program.methods
+        .flashLoan(new BN(1e6), Buffer.alloc(0))
+        .accountsPartial({
+          borrower: outsider.publicKey,
+          mint: mintX.publicKey,
+          config,
+          allowListEntry: entryFor(outsider.publicKey),
+          // Never called: the loan is refused first.
+          receiver: SystemProgram.programId,
+        })
+        .signers([outsider])
+        .rpc(),
+      "NotAllowListed"
+    );
+  });
+
+  it("Cuts a wallet off once it is removed from the list", async () => {
+    await program.methods
+      .removeFromAllowList()
+      .accountsPartial({
+        authority: provider.publicKey,
+        config,
+        entry: entryFor(listed.publicKey),
+      })
+      .rpc();
+
+    await expectError(
+      program.methods
+        .swap(true, new BN(1e6), new BN(1))
+        .accountsPartial(accountsFor(listed))
+        .signers([listed])
+        .rpc(),
+      "NotAllowListed"
+    );
+
+    await expectError(
+      program.methods
+        .withdraw(new BN(1e5), new BN(0), new BN(1))
+        .accountsPartial(accountsFor(listed))
+        .signers([listed])
+        .rpc(),
+      "NotAllowListed"
+    );
+  });
+});
@@ -1,20 +1,225 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
 import { AnchorAmm } from "../target/types/anchor_amm";
+import { Keypair, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
 
 describe("anchor_amm", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
 
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
   const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
 
+  const fee = 30; // 0.3% fee tier
+
+  const [mintA, mintB] = Array.from({ length: 2 }, () => Keypair.generate());
+
+  // The pool for a pair lives at the config PDA of its sorted mints, the
+  // same address `Config::find_pool_address` derives on chain.
+  const findPoolAddress = (a: PublicKey, b: PublicKey) => {
+    const [x, y] = [a, b].sort((l, r) => l.toBuffer().compare(r.toBuffer()));
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("config"),
+        x.toBuffer(),
+        y.toBuffer(),
+        new BN(fee).toArrayLike(Buffer, "le", 2),
+      ],
+      program.programId
+    )[0];
+  };
+
+  // Initializes a pool with `mintX` and `mintY` as given, at the config PDA
+  // for that order.
+  const initialize = (mintX: PublicKey, mintY: PublicKey) =>
+    program.methods
+      .initialize(fee, null, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX,
+        mintY,
+        config: PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("config"),
+            mintX.toBuffer(),
+            mintY.toBuffer(),
+            new BN(fee).toArrayLike(Buffer, "le", 2),
+          ],
+          program.programId
+        )[0],
+      })
+      .rpc();
+
+  const events = new anchor.EventParser(
+    program.programId,
+    new anchor.BorshCoder(program.idl)
+  );
+
+  // Events emitted by the transaction `signature`, by name.
+  const eventsOf = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const found = {};
+    for (const event of events.parseLogs(tx.meta.logMessages)) {
+      found[event.name] = event.data;
+    }
+    return found as { [name: string]: any };
+  };
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const expectFailure = async (tx: Promise<unknown>) => {
+    try {
+      await tx;
+    } catch {
+      return;
+    }
+    expect.fail("expected the transaction to fail");
+  };
+
   it("Is initialized!", async () => {
-    // Add your test here.
-    const seed = new anchor.BN(12345);
-    const fee = 30; // 0.3% fee
-    const authority = anchor.web3.Keypair.generate().publicKey;
-    
-    const tx = await program.methods.initialize(seed, fee, authority).rpc();
-    console.log("Your transaction signature", tx);
+    const lamports = await getMinimumBalanceForRentExemptMint(
+      provider.connection
+    );
+    const tx = new Transaction();
+    tx.instructions = [mintA, mintB].flatMap((mint) => [
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mint.publicKey,
+        lamports,
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(
+        mint.publicKey,
+        6,
+        provider.publicKey,
+        null
+      ),
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        getAssociatedTokenAddressSync(mint.publicKey, provider.publicKey),
+        provider.publicKey,
+        mint.publicKey
+      ),
+      createMintToInstruction(
+        mint.publicKey,
+        getAssociatedTokenAddressSync(mint.publicKey, provider.publicKey),
+        provider.publicKey,
+        1e10
+      ),
+    ]);
+    await provider.sendAndConfirm(tx, [mintA, mintB]);
+
+    const [x, y] = [mintA.publicKey, mintB.publicKey].sort((l, r) =>
+      l.toBuffer().compare(r.toBuffer())
+    );
+    const signature = await initialize(x, y);
+    console.log("Your transaction signature", signature);
+  });
+
+  it("Finds the pool at the same address for either mint order", async () => {
+    const pool = findPoolAddress(mintA.publicKey, mintB.publicKey);
+    expect(findPoolAddress(mintB.publicKey, mintA.publicKey).equals(pool)).to
+      .be.true;
+
+    const state = await program.account.config.fetch(pool);
+    const [x, y] = [state.mintX, state.mintY];
+    expect(x.toBuffer().compare(y.toBuffer())).to.be.lessThan(0);
+    expect([x, y].some((mint) => mint.equals(mintA.publicKey))).to.be.true;
+    expect([x, y].some((mint) => mint.equals(mintB.publicKey))).to.be.true;
+  });
+
+  it("Rejects a second pool for the pair in either mint order", async () => {
+    const [x, y] = [mintA.publicKey, mintB.publicKey].sort((l, r) =>
+      l.toBuffer().compare(r.toBuffer())
+    );
+
+    // Same order: the config account already exists.
+    await expectFailure(initialize(x, y));
+
+    // Reversed order: a different PDA, but the mints aren't sorted.
+    await expectError(initialize(y, x), "UnsortedMints");
+  });
+
+  it("Emits LiquidityAdded for a deposit", async () => {
+    const pool = findPoolAddress(mintA.publicKey, mintB.publicKey);
+    const state = await program.account.config.fetch(pool);
+
+    const signature = await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial({
+        user: provider.publicKey,
+        mintX: state.mintX,
+        mintY: state.mintY,
+        config: pool,
+        allowListEntry: null,
+      })
+      .rpc({ commitment: "confirmed" });
+    const { liquidityAdded } = await eventsOf(signature);
+
+    // The first deposit mints sqrt(x * y), including the locked minimum.
+    expect(liquidityAdded.config.equals(pool)).to.be.true;
+    expect(liquidityAdded.user.equals(provider.publicKey)).to.be.true;
+    for (const field of [
+      "amountX",
+      "amountY",
+      "lpMinted",
+      "reserveX",
+      "reserveY",
+      "lpSupply",
+    ]) {
+      expect(liquidityAdded[field].toNumber(), field).to.equal(1e9);
+    }
+  });
+
+  it("Emits Swapped for a swap", async () => {
+    const pool = findPoolAddress(mintA.publicKey, mintB.publicKey);
+    const state = await program.account.config.fetch(pool);
+
+    const signature = await program.methods
+      .swap(true, new BN(1e6), new BN(1))
+      .accountsPartial({
+        user: provider.publicKey,
+        mintX: state.mintX,
+        mintY: state.mintY,
+        config: pool,
+        allowListEntry: null,
+      })
+      .rpc({ commitment: "confirmed" });
+    const { swapped } = await eventsOf(signature);
+
+    // 0.3% of the input is kept as the fee, and the rest priced on x * y.
+    const afterFee = 1e6 - 3_000;
+    const amountOut = Math.floor((1e9 * afterFee) / (1e9 + afterFee));
+    expect(swapped.config.equals(pool)).to.be.true;
+    expect(swapped.user.equals(provider.publicKey)).to.be.true;
+    expect(swapped.isX).to.be.true;
+    expect(swapped.amountIn.toNumber()).to.equal(1e6);
+    expect(swapped.fee.toNumber()).to.equal(3_000);
+    expect(swapped.amountOut.toNumber()).to.equal(amountOut);
+    expect(swapped.reserveX.toNumber()).to.equal(1e9 + 1e6);
+    expect(swapped.reserveY.toNumber()).to.equal(1e9 - amountOut);
   });
 });
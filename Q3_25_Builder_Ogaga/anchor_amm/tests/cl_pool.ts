@@ -0,0 +1,321 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("concentrated liquidity pools", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  // 0.3% tier, tick spacing 60, so each tick array covers 1920 ticks.
+  const fee = 30;
+  const ticksPerArray = 60 * 32;
+
+  // sqrt(1) as Q64.64: the pool starts at tick 0.
+  const sqrtPriceOne = new BN(1).shln(64);
+
+  const [lp, attacker] = Array.from({ length: 2 }, () => Keypair.generate());
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const i32 = (n: number) => new BN(n).toTwos(32).toArrayLike(Buffer, "le", 4);
+
+  const pool = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("cl_pool"),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const tickArray = (start: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("tick_array"), pool.toBuffer(), i32(start)],
+      program.programId
+    )[0];
+
+  const arrayFor = (tick: number) =>
+    tickArray(Math.floor(tick / ticksPerArray) * ticksPerArray);
+
+  const positionFor = (owner: Keypair, lower: number, upper: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("position"),
+        pool.toBuffer(),
+        owner.publicKey.toBuffer(),
+        i32(lower),
+        i32(upper),
+      ],
+      program.programId
+    )[0];
+
+  const ata = (mint: Keypair, owner: Keypair) =>
+    getAssociatedTokenAddressSync(mint.publicKey, owner.publicKey);
+
+  const balance = async (mint: Keypair, owner: Keypair) =>
+    new BN((await getAccount(connection, ata(mint, owner))).amount.toString());
+
+  const modifyAccounts = (owner: Keypair, lower: number, upper: number) => ({
+    owner: owner.publicKey,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    pool,
+    position: positionFor(owner, lower, upper),
+    tickArrayLower: arrayFor(lower),
+    tickArrayUpper: arrayFor(upper),
+  });
+
+  const openPosition = (owner: Keypair, lower: number, upper: number) =>
+    program.methods
+      .openPosition(lower, upper)
+      .accountsPartial({
+        owner: owner.publicKey,
+        pool,
+        position: positionFor(owner, lower, upper),
+      })
+      .signers([owner])
+      .rpc();
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const swapAccounts = (user: Keypair) => ({
+    user: user.publicKey,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    pool,
+  });
+
+  it("Airdrop and create mints", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[lp, attacker].map((account) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: account.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[lp, attacker].flatMap((user) =>
+        [mintX, mintY].flatMap((mint) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, user),
+            user.publicKey,
+            mint.publicKey
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, user),
+            provider.publicKey,
+            1e12
+          ),
+        ])
+      ),
+    ];
+
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+  });
+
+  it("Initializes a pool and the tick arrays around its price", async () => {
+    await program.methods
+      .initializeClPool(fee, sqrtPriceOne)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        pool,
+      })
+      .rpc();
+
+    for (const start of [-ticksPerArray, 0]) {
+      await program.methods
+        .initializeTickArray(start)
+        .accountsPartial({ payer: provider.publicKey, pool, tickArray: tickArray(start) })
+        .rpc();
+    }
+
+    const state = await program.account.clPool.fetch(pool);
+    expect(state.tickCurrent).to.equal(0);
+    expect(state.tickSpacing).to.equal(60);
+  });
+
+  it("Rejects a fee that isn't one of the supported tiers", async () => {
+    const unsupported = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("cl_pool"),
+        mintX.publicKey.toBuffer(),
+        mintY.publicKey.toBuffer(),
+        new BN(25).toArrayLike(Buffer, "le", 2),
+      ],
+      program.programId
+    )[0];
+
+    await expectError(
+      program.methods
+        .initializeClPool(25, sqrtPriceOne)
+        .accountsPartial({
+          initializer: provider.publicKey,
+          mintX: mintX.publicKey,
+          mintY: mintY.publicKey,
+          pool: unsupported,
+        })
+        .rpc(),
+      "InvalidFeeTier"
+    );
+  });
+
+  it("Rejects ranges that aren't aligned to the tick spacing", async () => {
+    await expectError(openPosition(lp, -610, 600), "InvalidTickRange");
+    await expectError(openPosition(lp, 600, -600), "InvalidTickRange");
+  });
+
+  it("Provides liquidity and earns fees from a swap", async () => {
+    await openPosition(lp, -600, 600);
+    await program.methods
+      .increaseLiquidity(new BN(1e10), new BN(1e12), new BN(1e12))
+      .accountsPartial(modifyAccounts(lp, -600, 600))
+      .signers([lp])
+      .rpc();
+
+    await program.methods
+      .clSwap(true, new BN(1e7), new BN(1), new BN(0))
+      .accountsPartial(swapAccounts(lp))
+      .remainingAccounts(
+        [0, -ticksPerArray].map((start) => ({
+          pubkey: tickArray(start),
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .signers([lp])
+      .rpc();
+
+    const state = await program.account.clPool.fetch(pool);
+    expect(state.tickCurrent).to.be.below(0);
+    expect(state.feeGrowthGlobalX.gtn(0)).to.equal(true);
+
+    const position = await program.account.position.fetch(positionFor(lp, -600, 600));
+    expect(position.liquidity.toString()).to.equal("10000000000");
+  });
+
+  it("Enforces the swap's minimum output", async () => {
+    await expectError(
+      program.methods
+        .clSwap(false, new BN(1e6), new BN(2e6), new BN(0))
+        .accountsPartial(swapAccounts(lp))
+        .remainingAccounts([
+          { pubkey: tickArray(-ticksPerArray), isWritable: true, isSigner: false },
+          { pubkey: tickArray(0), isWritable: true, isSigner: false },
+        ])
+        .signers([lp])
+        .rpc(),
+      "SlippageExceeded"
+    );
+  });
+
+  it("Credits no fees to a range opened and emptied after they were earned", async () => {
+    // Neither tick is used by another position: the lower one is at or
+    // below the current tick and starts with all past growth outside it.
+    const [lower, upper] = [-1200, 1200];
+    const before = [await balance(mintX, attacker), await balance(mintY, attacker)];
+
+    await openPosition(attacker, lower, upper);
+    await program.methods
+      .increaseLiquidity(new BN(1e10), new BN(1e12), new BN(1e12))
+      .accountsPartial(modifyAccounts(attacker, lower, upper))
+      .signers([attacker])
+      .rpc();
+    await program.methods
+      .decreaseLiquidity(new BN(1e10), new BN(0), new BN(0))
+      .accountsPartial(modifyAccounts(attacker, lower, upper))
+      .signers([attacker])
+      .rpc();
+
+    const position = await program.account.position.fetch(positionFor(attacker, lower, upper));
+    expect(position.tokensOwedX.toNumber()).to.equal(0);
+    expect(position.tokensOwedY.toNumber()).to.equal(0);
+
+    await program.methods
+      .collectFees()
+      .accountsPartial(modifyAccounts(attacker, lower, upper))
+      .signers([attacker])
+      .rpc();
+
+    // Deposits round up and withdrawals round down, so the attacker can only
+    // have lost dust.
+    const after = [await balance(mintX, attacker), await balance(mintY, attacker)];
+    expect(after[0].lte(before[0])).to.equal(true);
+    expect(after[1].lte(before[1])).to.equal(true);
+
+    // Emptied ticks are cleared once the fees are settled.
+    const array = await program.account.tickArray.fetch(arrayFor(lower));
+    const tick = array.ticks[(lower + ticksPerArray) / 60];
+    expect(tick.liquidityGross.toNumber()).to.equal(0);
+    expect(tick.feeGrowthOutsideX.toNumber()).to.equal(0);
+  });
+
+  it("Pays the LP its share of the swap fee", async () => {
+    const before = await balance(mintX, lp);
+
+    await program.methods
+      .collectFees()
+      .accountsPartial(modifyAccounts(lp, -600, 600))
+      .signers([lp])
+      .rpc();
+
+    // 0.3% of the 1e7 swapped in, less rounding.
+    const earned = (await balance(mintX, lp)).sub(before).toNumber();
+    expect(earned).to.be.within(29_000, 30_000);
+  });
+});
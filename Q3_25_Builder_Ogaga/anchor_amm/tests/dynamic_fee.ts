@@ -0,0 +1,227 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("dynamic fees", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const fee = 30;
+
+  // The dynamic fee runs from 0.05% in calm markets up to 1%.
+  const [minFee, maxFee] = [5, 100];
+
+  const trader = Keypair.generate();
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const mintLp = PublicKey.findProgramAddressSync(
+    [Buffer.from("lp"), config.toBuffer()],
+    program.programId
+  )[0];
+
+  const ata = (mint: Keypair, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint.publicKey, owner);
+
+  const balance = async (account: PublicKey) =>
+    new BN((await getAccount(connection, account)).amount.toString());
+
+  const poolAccounts = (user: PublicKey) => ({
+    user,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    config,
+    allowListEntry: null,
+  });
+
+  const events = new anchor.EventParser(
+    program.programId,
+    new anchor.BorshCoder(program.idl)
+  );
+
+  // Events emitted by the transaction `signature`, by name.
+  const eventsOf = async (signature: string) => {
+    const tx = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const found = {};
+    for (const event of events.parseLogs(tx.meta.logMessages)) {
+      found[event.name] = event.data;
+    }
+    return found as { [name: string]: any };
+  };
+
+  // Sells `amount` of X and returns the fee its Swapped event reports.
+  const swapFee = async (amount: number) => {
+    const signature = await program.methods
+      .swap(true, new BN(amount), new BN(1))
+      .accountsPartial(poolAccounts(trader.publicKey))
+      .signers([trader])
+      .rpc({ commitment: "confirmed" });
+
+    const { swapped } = await eventsOf(signature);
+    expect(swapped, "no Swapped event").to.not.equal(undefined);
+    return swapped.fee.toNumber();
+  };
+
+  it("Airdrop, create mints and seed the pool", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: trader.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      }),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[trader.publicKey, provider.publicKey].flatMap((wallet) =>
+        [mintX, mintY].flatMap((mint) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, wallet),
+            wallet,
+            mint.publicKey
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, wallet),
+            provider.publicKey,
+            1e10
+          ),
+        ])
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+
+    await program.methods
+      .initialize(fee, provider.publicKey, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .rpc();
+
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial(poolAccounts(provider.publicKey))
+      .rpc();
+
+    await program.methods
+      .setDynamicFee(true, minFee, maxFee)
+      .accountsPartial({ authority: provider.publicKey, config })
+      .rpc();
+  });
+
+  it("Charges the minimum fee while the price is calm", async () => {
+    // 0.05% of 1e6.
+    expect(await swapFee(1e6)).to.equal(500);
+  });
+
+  it("Raises the fee after a large price move, up to the maximum", async () => {
+    // Moves the price by over 40%, far past what the maximum fee needs.
+    await swapFee(2e8);
+
+    const state = await program.account.config.fetch(config);
+    expect(state.volatility.toNumber()).to.be.greaterThan(
+      (maxFee - minFee) * 10
+    );
+
+    // 1% of 1e6, rather than the pool's 0.3% tier.
+    expect(await swapFee(1e6)).to.equal(10_000);
+  });
+
+  it("Zaps in taking only the tokens its LP is worth", async () => {
+    const [x, y] = await Promise.all(
+      [mintX, mintY].map((mint) => balance(ata(mint, trader.publicKey)))
+    );
+
+    const signature = await program.methods
+      .depositSingle(true, new BN(1e7), new BN(1))
+      .accountsPartial(poolAccounts(trader.publicKey))
+      .signers([trader])
+      .rpc({ commitment: "confirmed" });
+    const { swapped, liquidityAdded } = await eventsOf(signature);
+
+    // The trader paid the swap input and the X deposit, and kept whatever Y
+    // the swap returned beyond the Y deposit.
+    const [xAfter, yAfter, lp] = await Promise.all([
+      balance(ata(mintX, trader.publicKey)),
+      balance(ata(mintY, trader.publicKey)),
+      balance(getAssociatedTokenAddressSync(mintLp, trader.publicKey)),
+    ]);
+    expect(x.sub(xAfter).toString()).to.equal(
+      swapped.amountIn.add(liquidityAdded.amountX).toString()
+    );
+    expect(yAfter.sub(y).toString()).to.equal(
+      swapped.amountOut.sub(liquidityAdded.amountY).toString()
+    );
+    expect(lp.toString()).to.equal(liquidityAdded.lpMinted.toString());
+
+    // Each side deposited is exactly what the minted LP is worth, rounded up.
+    const minted = liquidityAdded.lpMinted;
+    const supply = liquidityAdded.lpSupply.sub(minted);
+    const worth = (reserve: BN, deposited: BN) => {
+      const owed = reserve.sub(deposited).mul(minted);
+      return owed.add(supply).subn(1).div(supply);
+    };
+    expect(liquidityAdded.amountX.toString()).to.equal(
+      worth(liquidityAdded.reserveX, liquidityAdded.amountX).toString()
+    );
+    expect(liquidityAdded.amountY.toString()).to.equal(
+      worth(liquidityAdded.reserveY, liquidityAdded.amountY).toString()
+    );
+  });
+});
@@ -0,0 +1,206 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import { FlashReceiver } from "../target/types/flash_receiver";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("flash loans", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const receiver = anchor.workspace.flashReceiver as Program<FlashReceiver>;
+
+  const fee = 30;
+
+  const borrower = Keypair.generate();
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const mintLp = PublicKey.findProgramAddressSync(
+    [Buffer.from("lp"), config.toBuffer()],
+    program.programId
+  )[0];
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true);
+
+  const vault = ata(mintX.publicKey, config);
+
+  const borrowerAta = ata(mintX.publicKey, borrower.publicKey);
+
+  const balance = async (account: PublicKey) =>
+    Number((await getAccount(connection, account)).amount);
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Borrows `amount` of X and has the receiver hand back `repay`.
+  const flashLoan = async (amount: number, repay: number) => {
+    const ix = await receiver.methods
+      .repay(new BN(repay), Buffer.alloc(0))
+      .accounts({
+        borrower: borrower.publicKey,
+        borrowerAta,
+        vault,
+      })
+      .instruction();
+
+    return program.methods
+      .flashLoan(new BN(amount), ix.data)
+      .accountsPartial({
+        borrower: borrower.publicKey,
+        mint: mintX.publicKey,
+        config,
+        allowListEntry: null,
+        receiver: receiver.programId,
+      })
+      .remainingAccounts(ix.keys)
+      .signers([borrower])
+      .rpc();
+  };
+
+  it("Airdrop, create mints and seed the pool", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: borrower.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      }),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          ata(mint.publicKey, provider.publicKey),
+          provider.publicKey,
+          mint.publicKey
+        ),
+        createMintToInstruction(
+          mint.publicKey,
+          ata(mint.publicKey, provider.publicKey),
+          provider.publicKey,
+          1e10
+        ),
+      ]),
+      // The borrower only holds enough X to cover fees.
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        borrowerAta,
+        borrower.publicKey,
+        mintX.publicKey
+      ),
+      createMintToInstruction(
+        mintX.publicKey,
+        borrowerAta,
+        provider.publicKey,
+        1e6
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+
+    await program.methods
+      .initialize(fee, provider.publicKey, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .rpc();
+
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial({
+        user: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+        allowListEntry: null,
+      })
+      .rpc();
+  });
+
+  it("Lends when the loan comes back with its fee", async () => {
+    const reserves = await balance(vault);
+    const supply = (await connection.getTokenSupply(mintLp)).value.amount;
+
+    // 0.3% of 1e8.
+    await flashLoan(1e8, 1e8 + 300_000);
+
+    // The fee stays in the vault for LPs, without minting them anything.
+    expect(await balance(vault)).to.equal(reserves + 300_000);
+    expect(await balance(borrowerAta)).to.equal(1e6 - 300_000);
+    expect((await connection.getTokenSupply(mintLp)).value.amount).to.equal(
+      supply
+    );
+
+    const state = await program.account.config.fetch(config);
+    expect(state.flashLoanActive).to.equal(false);
+  });
+
+  it("Reverts a loan that comes back short", async () => {
+    const reserves = await balance(vault);
+    const held = await balance(borrowerAta);
+
+    // The principal alone isn't enough.
+    await expectError(flashLoan(1e8, 1e8), "FlashLoanNotRepaid");
+
+    expect(await balance(vault)).to.equal(reserves);
+    expect(await balance(borrowerAta)).to.equal(held);
+  });
+});
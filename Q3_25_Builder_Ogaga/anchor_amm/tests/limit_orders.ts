@@ -0,0 +1,345 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  createTransferInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("limit orders", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const fee = 30;
+  const tip = 0.01 * LAMPORTS_PER_SOL;
+
+  const [owner, cranker, stranger] = Array.from({ length: 3 }, () =>
+    Keypair.generate()
+  );
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const orderFor = (orderId: number, wallet = owner) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("order"),
+        config.toBuffer(),
+        wallet.publicKey.toBuffer(),
+        new BN(orderId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const ata = (mint: Keypair, wallet: PublicKey) =>
+    getAssociatedTokenAddressSync(mint.publicKey, wallet, true);
+
+  const balance = async (mint: Keypair, wallet: PublicKey) =>
+    Number((await getAccount(connection, ata(mint, wallet))).amount);
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Every order sells 1e6 X for Y at a pool price of about 1.
+  const placeOrder = (
+    orderId: number,
+    minAmountOut: number,
+    expiresIn: number,
+    wallet = owner
+  ) =>
+    program.methods
+      .placeOrder(
+        new BN(orderId),
+        new BN(1e6),
+        new BN(minAmountOut),
+        new BN(tip),
+        new BN(Math.floor(Date.now() / 1000) + expiresIn)
+      )
+      .accountsPartial({
+        owner: wallet.publicKey,
+        mintIn: mintX.publicKey,
+        mintOut: mintY.publicKey,
+        config,
+        order: orderFor(orderId, wallet),
+        allowListEntry: null,
+      })
+      .signers([wallet])
+      .rpc();
+
+  const fillAccounts = (orderId: number, wallet = owner) => [
+    orderFor(orderId, wallet),
+    ata(mintX, orderFor(orderId, wallet)),
+    ata(mintY, wallet.publicKey),
+    wallet.publicKey,
+  ].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }));
+
+  const cancelOrder = (orderId: number, signer: Keypair, wallet = owner) =>
+    program.methods
+      .cancelOrder()
+      .accountsPartial({
+        signer: signer.publicKey,
+        owner: wallet.publicKey,
+        mintIn: mintX.publicKey,
+        order: orderFor(orderId, wallet),
+      })
+      .signers([signer])
+      .rpc();
+
+  const [crossed, uncrossed, expiring, unpayable, behindUnpayable] = [
+    1, 2, 3, 4, 5,
+  ];
+
+  it("Airdrop, create mints and seed a pool", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[owner, cranker, stranger].map((account) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: account.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[owner.publicKey, provider.publicKey].flatMap((wallet) =>
+        [mintX, mintY].flatMap((mint) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, wallet),
+            wallet,
+            mint.publicKey
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, wallet),
+            provider.publicKey,
+            1e10
+          ),
+        ])
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+
+    await program.methods
+      .initialize(fee, null, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .rpc();
+
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial({
+        user: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+        allowListEntry: null,
+      })
+      .rpc();
+  });
+
+  it("Places orders, holding the input and the tip", async () => {
+    const before = await balance(mintX, owner.publicKey);
+
+    await placeOrder(crossed, 9.8e5, 3600);
+    await placeOrder(uncrossed, 2e6, 3600);
+    await placeOrder(expiring, 9.8e5, 2);
+
+    expect(before - (await balance(mintX, owner.publicKey))).to.equal(3e6);
+    expect(await balance(mintX, orderFor(crossed))).to.equal(1e6);
+
+    const order = await program.account.limitOrder.fetch(orderFor(crossed));
+    expect(order.isX).to.equal(true);
+    expect(order.tip.toNumber()).to.equal(tip);
+  });
+
+  it("Rejects an order whose mints aren't the pool's pair", async () => {
+    await expectError(
+      program.methods
+        .placeOrder(new BN(9), new BN(1e6), new BN(1), new BN(0), new BN(Math.floor(Date.now() / 1000) + 3600))
+        .accountsPartial({
+          owner: owner.publicKey,
+          mintIn: mintX.publicKey,
+          mintOut: mintX.publicKey,
+          config,
+          order: orderFor(9),
+          allowListEntry: null,
+        })
+        .signers([owner])
+        .rpc(),
+      "InvalidToken"
+    );
+  });
+
+  it("Lets only the owner cancel an order before it expires", async () => {
+    await expectError(cancelOrder(uncrossed, stranger), "OrderNotExpired");
+  });
+
+  it("Fills crossed orders and skips the rest, paying the cranker", async () => {
+    // Let the short-lived order lapse.
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    // Tokens sent to an order's vault don't stop it from being filled.
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createTransferInstruction(
+          ata(mintX, provider.publicKey),
+          ata(mintX, orderFor(crossed)),
+          provider.publicKey,
+          1
+        )
+      )
+    );
+
+    const ownerY = await balance(mintY, owner.publicKey);
+    const crankerLamports = await connection.getBalance(cranker.publicKey);
+
+    await program.methods
+      .fillOrders()
+      .accountsPartial({
+        cranker: cranker.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .remainingAccounts([
+        ...fillAccounts(crossed),
+        ...fillAccounts(uncrossed),
+        ...fillAccounts(expiring),
+      ])
+      .signers([cranker])
+      .rpc();
+
+    const received = (await balance(mintY, owner.publicKey)) - ownerY;
+    expect(received).to.be.at.least(9.8e5);
+    expect(await connection.getAccountInfo(orderFor(crossed))).to.equal(null);
+    expect(await connection.getAccountInfo(ata(mintX, orderFor(crossed)))).to.equal(null);
+
+    // The provider wallet pays the transaction fee, so this is all tip.
+    const earned = (await connection.getBalance(cranker.publicKey)) - crankerLamports;
+    expect(earned).to.equal(tip);
+
+    // Neither the uncrossed nor the expired order was touched.
+    expect(await balance(mintX, orderFor(uncrossed))).to.equal(1e6);
+    expect(await balance(mintX, orderFor(expiring))).to.equal(1e6);
+  });
+
+  it("Skips an order with nowhere to pay out without blocking the batch", async () => {
+    // The stranger holds X but has no Y account for the order to fill into.
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          ata(mintX, stranger.publicKey),
+          stranger.publicKey,
+          mintX.publicKey
+        ),
+        createMintToInstruction(
+          mintX.publicKey,
+          ata(mintX, stranger.publicKey),
+          provider.publicKey,
+          1e6
+        )
+      )
+    );
+    await placeOrder(unpayable, 9.8e5, 3600, stranger);
+    await placeOrder(behindUnpayable, 9.8e5, 3600);
+
+    await program.methods
+      .fillOrders()
+      .accountsPartial({
+        cranker: cranker.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .remainingAccounts([
+        ...fillAccounts(unpayable, stranger),
+        ...fillAccounts(behindUnpayable),
+      ])
+      .signers([cranker])
+      .rpc();
+
+    expect(await balance(mintX, orderFor(unpayable, stranger))).to.equal(1e6);
+    expect(await connection.getAccountInfo(orderFor(behindUnpayable))).to.equal(null);
+
+    // The stranger can still get their tokens back.
+    await cancelOrder(unpayable, stranger, stranger);
+    expect(await balance(mintX, stranger.publicKey)).to.equal(1e6);
+  });
+
+  it("Lets anyone clean up an expired order, refunding the owner", async () => {
+    const before = await balance(mintX, owner.publicKey);
+
+    await cancelOrder(expiring, stranger);
+
+    expect((await balance(mintX, owner.publicKey)) - before).to.equal(1e6);
+    expect(await connection.getAccountInfo(orderFor(expiring))).to.equal(null);
+  });
+
+  it("Lets the owner cancel an open order", async () => {
+    const before = await balance(mintX, owner.publicKey);
+
+    await cancelOrder(uncrossed, owner);
+
+    expect((await balance(mintX, owner.publicKey)) - before).to.equal(1e6);
+    expect(await connection.getAccountInfo(orderFor(uncrossed))).to.equal(null);
+  });
+});
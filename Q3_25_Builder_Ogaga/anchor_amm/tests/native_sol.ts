@@ -0,0 +1,292 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  NATIVE_MINT,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  createSyncNativeInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("native SOL pools", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const fee = 30;
+
+  // `lp` and `trader` only ever hold plain SOL; `holder` keeps wSOL.
+  const [lp, trader, holder] = Array.from({ length: 3 }, () =>
+    Keypair.generate()
+  );
+
+  const token = Keypair.generate();
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = [NATIVE_MINT, token.publicKey].sort((a, b) =>
+    a.toBuffer().compare(b.toBuffer())
+  );
+  const solIsX = mintX.equals(NATIVE_MINT);
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      mintX.toBuffer(),
+      mintY.toBuffer(),
+      new BN(fee).toArrayLike(Buffer, "le", 2),
+    ],
+    program.programId
+  )[0];
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true);
+
+  const balance = async (mint: PublicKey, owner: PublicKey) =>
+    Number((await getAccount(connection, ata(mint, owner))).amount);
+
+  const lamports = (owner: PublicKey) => connection.getBalance(owner);
+
+  const wsolClosed = async (owner: PublicKey) =>
+    (await connection.getAccountInfo(ata(NATIVE_MINT, owner))) === null;
+
+  const poolAccounts = (user: PublicKey) => ({
+    user,
+    mintX,
+    mintY,
+    config,
+    allowListEntry: null,
+  });
+
+  // Remaining accounts for a single-hop route from `from` into `to`. The
+  // pool is open to everyone, so the allow-list entry is never read.
+  const route = (user: PublicKey, from: PublicKey, to: PublicKey) =>
+    [
+      ata(from, user),
+      config,
+      ata(mintX, config),
+      ata(mintY, config),
+      ata(to, user),
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("allow"), config.toBuffer(), user.toBuffer()],
+        program.programId
+      )[0],
+    ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+  // Transaction fees are paid by the provider, so a wallet's lamports only
+  // move by what it wraps, unwraps and pays in rent.
+  const rent = 0.01 * LAMPORTS_PER_SOL;
+
+  it("Airdrop, create the token and seed the pool from a plain wallet", async () => {
+    const mintLamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[lp, trader, holder].map((account) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: account.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: token.publicKey,
+        lamports: mintLamports,
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(
+        token.publicKey,
+        9,
+        provider.publicKey,
+        null
+      ),
+      ...[lp.publicKey, trader.publicKey, holder.publicKey].flatMap(
+        (wallet) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(token.publicKey, wallet),
+            wallet,
+            token.publicKey
+          ),
+          createMintToInstruction(
+            token.publicKey,
+            ata(token.publicKey, wallet),
+            provider.publicKey,
+            1e10
+          ),
+        ]
+      ),
+      // The holder already keeps 2 SOL wrapped.
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        ata(NATIVE_MINT, holder.publicKey),
+        holder.publicKey,
+        NATIVE_MINT
+      ),
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: ata(NATIVE_MINT, holder.publicKey),
+        lamports: 2 * LAMPORTS_PER_SOL,
+      }),
+      createSyncNativeInstruction(ata(NATIVE_MINT, holder.publicKey)),
+    ];
+    await provider.sendAndConfirm(tx, [token]);
+
+    await program.methods
+      .initialize(fee, provider.publicKey, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX,
+        mintY,
+        config,
+      })
+      .rpc();
+
+    const before = await lamports(lp.publicKey);
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial(poolAccounts(lp.publicKey))
+      .signers([lp])
+      .rpc();
+
+    // 1 SOL was wrapped for the deposit and the temporary account closed;
+    // the rest is the LP token account's rent.
+    const spent = before - (await lamports(lp.publicKey));
+    expect(spent).to.be.greaterThanOrEqual(1e9);
+    expect(spent).to.be.lessThan(1e9 + rent);
+    expect(await wsolClosed(lp.publicKey)).to.equal(true);
+  });
+
+  it("Swaps SOL in and out of a plain wallet", async () => {
+    const before = await lamports(trader.publicKey);
+    await program.methods
+      .swap(solIsX, new BN(1e8), new BN(1))
+      .accountsPartial(poolAccounts(trader.publicKey))
+      .signers([trader])
+      .rpc();
+    expect(before - (await lamports(trader.publicKey))).to.equal(1e8);
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+
+    const tokens = await balance(token.publicKey, trader.publicKey);
+    await program.methods
+      .swap(!solIsX, new BN(1e8), new BN(1))
+      .accountsPartial(poolAccounts(trader.publicKey))
+      .signers([trader])
+      .rpc();
+    expect(tokens - (await balance(token.publicKey, trader.publicKey))).to.equal(1e8);
+    expect(await lamports(trader.publicKey)).to.be.greaterThan(before - 1e8);
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+  });
+
+  it("Spends wSOL a wallet already holds and leaves it wrapped", async () => {
+    const sol = await lamports(holder.publicKey);
+    const wsol = await balance(NATIVE_MINT, holder.publicKey);
+
+    await program.methods
+      .swap(solIsX, new BN(1e8), new BN(1))
+      .accountsPartial(poolAccounts(holder.publicKey))
+      .signers([holder])
+      .rpc();
+
+    // Paid from the wSOL already held, without touching the wallet's SOL.
+    expect(wsol - (await balance(NATIVE_MINT, holder.publicKey))).to.equal(1e8);
+    expect(await lamports(holder.publicKey)).to.equal(sol);
+
+    await program.methods
+      .swap(!solIsX, new BN(1e8), new BN(1))
+      .accountsPartial(poolAccounts(holder.publicKey))
+      .signers([holder])
+      .rpc();
+
+    // The proceeds stay wrapped alongside the rest.
+    expect(await balance(NATIVE_MINT, holder.publicKey)).to.be.greaterThan(
+      wsol - 1e8
+    );
+    expect(await lamports(holder.publicKey)).to.equal(sol);
+  });
+
+  it("Wraps only the shortfall above the wSOL a wallet holds", async () => {
+    const sol = await lamports(holder.publicKey);
+    const wsol = await balance(NATIVE_MINT, holder.publicKey);
+    const amount = wsol + 1e8;
+
+    await program.methods
+      .swap(solIsX, new BN(amount), new BN(1))
+      .accountsPartial(poolAccounts(holder.publicKey))
+      .signers([holder])
+      .rpc();
+
+    expect(await balance(NATIVE_MINT, holder.publicKey)).to.equal(0);
+    expect(sol - (await lamports(holder.publicKey))).to.equal(1e8);
+    expect(await wsolClosed(holder.publicKey)).to.equal(false);
+  });
+
+  it("Zaps tokens in without fronting SOL for the swapped side", async () => {
+    const before = await lamports(trader.publicKey);
+    const mintLp = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config.toBuffer()],
+      program.programId
+    )[0];
+
+    await program.methods
+      .depositSingle(!solIsX, new BN(1e8), new BN(1))
+      .accountsPartial(poolAccounts(trader.publicKey))
+      .signers([trader])
+      .rpc();
+
+    // The SOL half comes from the swap, and only dust (plus the wSOL
+    // account's rent, refunded on close) ever passes through the wallet.
+    expect(await balance(mintLp, trader.publicKey)).to.be.greaterThan(0);
+    expect(before - (await lamports(trader.publicKey))).to.be.lessThan(rent);
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+  });
+
+  it("Wraps and unwraps SOL at the ends of a route", async () => {
+    // The route creates the wSOL account it spends from, as a swap does.
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+    const before = await lamports(trader.publicKey);
+    await program.methods
+      .routeSwap(new BN(1e8), new BN(1))
+      .accounts({ user: trader.publicKey })
+      .remainingAccounts(route(trader.publicKey, NATIVE_MINT, token.publicKey))
+      .signers([trader])
+      .rpc();
+    expect(before - (await lamports(trader.publicKey))).to.equal(1e8);
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+
+    // A route's output account still has to exist.
+    const createWsol = createAssociatedTokenAccountIdempotentInstruction(
+      trader.publicKey,
+      ata(NATIVE_MINT, trader.publicKey),
+      trader.publicKey,
+      NATIVE_MINT
+    );
+    await program.methods
+      .routeSwap(new BN(1e8), new BN(1))
+      .accounts({ user: trader.publicKey })
+      .remainingAccounts(route(trader.publicKey, token.publicKey, NATIVE_MINT))
+      .preInstructions([createWsol])
+      .signers([trader])
+      .rpc();
+    expect(await lamports(trader.publicKey)).to.be.greaterThan(before - 1e8);
+    expect(await wsolClosed(trader.publicKey)).to.equal(true);
+  });
+});
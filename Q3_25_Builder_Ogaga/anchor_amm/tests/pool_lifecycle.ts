@@ -0,0 +1,292 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("sunset, close and migrate", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  // Liquidity moves out of the 0.3% pool into the 1% one. The 0.05% pool
+  // never takes any.
+  const [oldFee, newFee, emptyFee] = [30, 100, 5];
+
+  const [lp, stranger] = Array.from({ length: 2 }, () => Keypair.generate());
+
+  // Pools only accept mints in address order.
+  const [mintX, mintY] = Array.from({ length: 2 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const configFor = (fee: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("config"),
+        mintX.publicKey.toBuffer(),
+        mintY.publicKey.toBuffer(),
+        new BN(fee).toArrayLike(Buffer, "le", 2),
+      ],
+      program.programId
+    )[0];
+
+  const [oldConfig, newConfig, emptyConfig] = [
+    configFor(oldFee),
+    configFor(newFee),
+    configFor(emptyFee),
+  ];
+
+  const mintLpFor = (config: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), config.toBuffer()],
+      program.programId
+    )[0];
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true);
+
+  const balance = async (mint: PublicKey, owner: PublicKey) =>
+    Number((await getAccount(connection, ata(mint, owner))).amount);
+
+  const poolAccounts = (user: PublicKey, config: PublicKey) => ({
+    user,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    config,
+    allowListEntry: null,
+  });
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const initialize = (fee: number) =>
+    program.methods
+      .initialize(fee, provider.publicKey, { constantProduct: {} }, new BN(0), false)
+      .accountsPartial({
+        initializer: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config: configFor(fee),
+      })
+      .rpc();
+
+  const sunsetPool = (config: PublicKey) =>
+    program.methods
+      .sunsetPool()
+      .accountsPartial({ authority: provider.publicKey, config })
+      .rpc();
+
+  const closePool = (config: PublicKey) =>
+    program.methods
+      .closePool()
+      .accountsPartial({
+        authority: provider.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        config,
+      })
+      .rpc();
+
+  const migrate = (amount: number, minX: number, minY: number) =>
+    program.methods
+      .migrateLiquidity(new BN(amount), new BN(minX), new BN(minY), new BN(1))
+      .accountsPartial({
+        user: lp.publicKey,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        oldConfig,
+        newConfig,
+        oldAllowListEntry: null,
+        newAllowListEntry: null,
+      })
+      .signers([lp])
+      .rpc();
+
+  it("Airdrop, create mints and seed both pools", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      ...[lp, stranger].map((account) =>
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: account.publicKey,
+          lamports: 10 * LAMPORTS_PER_SOL,
+        })
+      ),
+      ...[mintX, mintY].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[lp.publicKey, stranger.publicKey, provider.publicKey].flatMap(
+        (wallet) =>
+          [mintX, mintY].flatMap((mint) => [
+            createAssociatedTokenAccountIdempotentInstruction(
+              provider.publicKey,
+              ata(mint.publicKey, wallet),
+              wallet,
+              mint.publicKey
+            ),
+            createMintToInstruction(
+              mint.publicKey,
+              ata(mint.publicKey, wallet),
+              provider.publicKey,
+              1e10
+            ),
+          ])
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+
+    await initialize(oldFee);
+    await initialize(newFee);
+    await initialize(emptyFee);
+
+    // The LP is the only depositor in the old pool.
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial(poolAccounts(lp.publicKey, oldConfig))
+      .signers([lp])
+      .rpc();
+    await program.methods
+      .deposit(new BN(1), new BN(1e9), new BN(1e9))
+      .accountsPartial(poolAccounts(provider.publicKey, newConfig))
+      .rpc();
+  });
+
+  it("Only the authority can sunset a pool", async () => {
+    await expectError(
+      program.methods
+        .sunsetPool()
+        .accountsPartial({ authority: stranger.publicKey, config: oldConfig })
+        .signers([stranger])
+        .rpc(),
+      "InvalidAuthority"
+    );
+
+    await sunsetPool(oldConfig);
+
+    const state = await program.account.config.fetch(oldConfig);
+    expect(state.sunset).to.equal(true);
+  });
+
+  it("Stops swaps and deposits once sunset", async () => {
+    await expectError(
+      program.methods
+        .swap(true, new BN(1e6), new BN(1))
+        .accountsPartial(poolAccounts(stranger.publicKey, oldConfig))
+        .signers([stranger])
+        .rpc(),
+      "PoolSunset"
+    );
+
+    await expectError(
+      program.methods
+        .deposit(new BN(1e6), new BN(1e8), new BN(1e8))
+        .accountsPartial(poolAccounts(stranger.publicKey, oldConfig))
+        .signers([stranger])
+        .rpc(),
+      "PoolSunset"
+    );
+  });
+
+  it("Still lets LPs withdraw after sunset", async () => {
+    const before = await balance(mintX.publicKey, lp.publicKey);
+
+    await program.methods
+      .withdraw(new BN(1e8), new BN(1), new BN(1))
+      .accountsPartial(poolAccounts(lp.publicKey, oldConfig))
+      .signers([lp])
+      .rpc();
+
+    expect((await balance(mintX.publicKey, lp.publicKey)) - before).to.equal(1e8);
+  });
+
+  it("Won't close a pool while LPs still hold shares", async () => {
+    await expectError(closePool(oldConfig), "PoolNotEmpty");
+  });
+
+  it("Won't migrate for less than the old pool's minimums", async () => {
+    const oldLp = await balance(mintLpFor(oldConfig), lp.publicKey);
+
+    // The LP's share of the old pool is a little under 9e8 of each token.
+    await expectError(migrate(oldLp, 9e8, 1), "SlippageExceeded");
+  });
+
+  it("Migrates the rest of the LP into the new pool", async () => {
+    const oldLp = await balance(mintLpFor(oldConfig), lp.publicKey);
+
+    await migrate(oldLp, 8.9e8, 8.9e8);
+
+    expect(await balance(mintLpFor(oldConfig), lp.publicKey)).to.equal(0);
+    // Both pools trade at 1:1, so the LP carries over one for one.
+    expect(await balance(mintLpFor(newConfig), lp.publicKey)).to.be.closeTo(oldLp, 1);
+  });
+
+  it("Won't close a pool still backed by its locked minimum liquidity", async () => {
+    await expectError(closePool(oldConfig), "PoolNotEmpty");
+  });
+
+  it("Closes a pool that never took liquidity, leaving a closed config behind", async () => {
+    await sunsetPool(emptyConfig);
+    await closePool(emptyConfig);
+
+    for (const vault of [
+      ata(mintX.publicKey, emptyConfig),
+      ata(mintY.publicKey, emptyConfig),
+    ]) {
+      expect(await connection.getAccountInfo(vault)).to.equal(null);
+    }
+
+    const state = await program.account.config.fetch(emptyConfig);
+    expect(state.closed).to.equal(true);
+  });
+
+  it("Can't re-create a closed pool's pair and fee tier", async () => {
+    let failed = false;
+    try {
+      await initialize(emptyFee);
+    } catch {
+      failed = true;
+    }
+    expect(failed).to.equal(true);
+  });
+});
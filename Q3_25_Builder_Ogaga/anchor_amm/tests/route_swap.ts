@@ -0,0 +1,221 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { AnchorAmm } from "../target/types/anchor_amm";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("multi-hop routes", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const connection = provider.connection;
+
+  const program = anchor.workspace.anchorAmm as Program<AnchorAmm>;
+
+  const fee = 30;
+
+  const trader = Keypair.generate();
+
+  // Pools only accept mints in address order, so sorting the three mints
+  // gives an A/B pool and a B/C pool to route A -> B -> C through.
+  const [mintA, mintB, mintC] = Array.from({ length: 3 }, () =>
+    Keypair.generate()
+  ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+  const configFor = (mintX: Keypair, mintY: Keypair) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("config"),
+        mintX.publicKey.toBuffer(),
+        mintY.publicKey.toBuffer(),
+        new BN(fee).toArrayLike(Buffer, "le", 2),
+      ],
+      program.programId
+    )[0];
+
+  const pools = [
+    { mintX: mintA, mintY: mintB, config: configFor(mintA, mintB) },
+    { mintX: mintB, mintY: mintC, config: configFor(mintB, mintC) },
+  ];
+
+  const ata = (mint: Keypair, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint.publicKey, owner, true);
+
+  const balance = async (account: PublicKey) =>
+    new BN((await getAccount(connection, account)).amount.toString());
+
+  const reserves = (pool: typeof pools[number]) =>
+    Promise.all([
+      balance(ata(pool.mintX, pool.config)),
+      balance(ata(pool.mintY, pool.config)),
+    ]);
+
+  const expectError = async (tx: Promise<unknown>, code: string) => {
+    try {
+      await tx;
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Remaining accounts for A -> B -> C. Neither pool is allow-listed, so the
+  // entries are never read.
+  const route = [
+    ata(mintA, trader.publicKey),
+    ...pools.flatMap((pool, hop) => [
+      pool.config,
+      ata(pool.mintX, pool.config),
+      ata(pool.mintY, pool.config),
+      ata([mintB, mintC][hop], trader.publicKey),
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("allow"), pool.config.toBuffer(), trader.publicKey.toBuffer()],
+        program.programId
+      )[0],
+    ]),
+  ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+  const routeSwap = (amountIn: BN, minAmountOut: BN) =>
+    program.methods
+      .routeSwap(amountIn, minAmountOut)
+      .accounts({ user: trader.publicKey })
+      .remainingAccounts(route)
+      .signers([trader])
+      .rpc();
+
+  // Constant-product output for `amountIn`, with the fee taken from the input.
+  const quote = (reserveIn: BN, reserveOut: BN, amountIn: BN) => {
+    const afterFee = amountIn.muln(10_000 - fee).divn(10_000);
+    return reserveOut.mul(afterFee).div(reserveIn.add(afterFee));
+  };
+
+  it("Airdrop, create mints and seed both pools", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    const tx = new Transaction();
+    tx.instructions = [
+      SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: trader.publicKey,
+        lamports: 10 * LAMPORTS_PER_SOL,
+      }),
+      ...[mintA, mintB, mintC].flatMap((mint) => [
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null
+        ),
+      ]),
+      ...[trader.publicKey, provider.publicKey].flatMap((wallet) =>
+        [mintA, mintB, mintC].flatMap((mint) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, wallet),
+            wallet,
+            mint.publicKey
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, wallet),
+            provider.publicKey,
+            1e10
+          ),
+        ])
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mintA, mintB, mintC]);
+
+    // A trades at 2 B, and B at 1 C.
+    for (const [pool, x, y] of [
+      [pools[0], 1e9, 2e9],
+      [pools[1], 2e9, 2e9],
+    ] as const) {
+      await program.methods
+        .initialize(fee, null, { constantProduct: {} }, new BN(0), false)
+        .accountsPartial({
+          initializer: provider.publicKey,
+          mintX: pool.mintX.publicKey,
+          mintY: pool.mintY.publicKey,
+          config: pool.config,
+        })
+        .rpc();
+
+      await program.methods
+        .deposit(new BN(1), new BN(x), new BN(y))
+        .accountsPartial({
+          user: provider.publicKey,
+          mintX: pool.mintX.publicKey,
+          mintY: pool.mintY.publicKey,
+          config: pool.config,
+          allowListEntry: null,
+        })
+        .rpc();
+    }
+  });
+
+  it("Rejects a route that pays out less than the minimum", async () => {
+    const [[a, b], [b2, c]] = await Promise.all(pools.map(reserves));
+    const amountIn = new BN(1e7);
+    const out = quote(b2, c, quote(a, b, amountIn));
+
+    await expectError(routeSwap(amountIn, out.addn(1)), "SlippageExceeded");
+  });
+
+  it("Routes A through B into C, keeping each pool's invariant", async () => {
+    const before = await Promise.all(pools.map(reserves));
+    const [[a, b], [b2, c]] = before;
+    const amountIn = new BN(1e7);
+
+    // What each hop pays out, priced on the reserves before the route.
+    const midOut = quote(a, b, amountIn);
+    const out = quote(b2, c, midOut);
+
+    const [spent, middle, received] = [mintA, mintB, mintC].map((mint) =>
+      ata(mint, trader.publicKey)
+    );
+    const held = await Promise.all([spent, middle, received].map(balance));
+
+    await routeSwap(amountIn, out);
+
+    const after = await Promise.all([spent, middle, received].map(balance));
+    expect(held[0].sub(after[0]).toString()).to.equal(amountIn.toString());
+    // Everything B bought goes straight on into the second hop.
+    expect(after[1].toString()).to.equal(held[1].toString());
+    expect(after[2].sub(held[2]).toString()).to.equal(out.toString());
+
+    const reservesAfter = await Promise.all(pools.map(reserves));
+    for (const [hop, [x, y]] of before.entries()) {
+      const [xAfter, yAfter] = reservesAfter[hop];
+      expect(xAfter.mul(yAfter).gte(x.mul(y))).to.equal(true);
+    }
+    expect(reservesAfter[0][0].sub(a).toString()).to.equal(amountIn.toString());
+    expect(b.sub(reservesAfter[0][1]).toString()).to.equal(midOut.toString());
+    expect(reservesAfter[1][0].sub(b2).toString()).to.equal(midOut.toString());
+    expect(c.sub(reservesAfter[1][1]).toString()).to.equal(out.toString());
+  });
+});
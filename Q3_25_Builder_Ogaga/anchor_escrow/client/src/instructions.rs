@@ -0,0 +1,127 @@
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, system_program, InstructionData,
+    ToAccountMetas,
+};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+
+use anchor_escrow::{accounts, instruction, Escrow, PoolPrice};
+
+use crate::pda::{ata_address, config_address, escrow_address, vault_address};
+
+/// Terms of a new offer. See `anchor_escrow::make`.
+#[derive(Clone, Copy, Default)]
+pub struct MakeArgs {
+    pub seed: u64,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: i64,
+    pub cleanup_reward: u64,
+    pub taker: Option<Pubkey>,
+    pub price: Option<PoolPrice>,
+}
+
+pub fn make(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+    args: MakeArgs,
+) -> Instruction {
+    let (escrow, _) = escrow_address(maker, args.seed);
+
+    let accounts = accounts::Make {
+        maker: *maker,
+        mint_a: *mint_a,
+        mint_b: *mint_b,
+        maker_ata_a: ata_address(maker, mint_a, token_program),
+        escrow,
+        vault: vault_address(&escrow, mint_a, token_program),
+        associated_token_program: associated_token::ID,
+        token_program: *token_program,
+        system_program: system_program::ID,
+    };
+
+    let data = instruction::Make {
+        seed: args.seed,
+        deposit: args.deposit,
+        receive: args.receive,
+        expires_at: args.expires_at,
+        cleanup_reward: args.cleanup_reward,
+        taker: args.taker,
+        price: args.price,
+    };
+
+    Instruction {
+        program_id: anchor_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Takes `escrow` in full, paying at most `max_amount_b`. `fee_recipient`
+/// comes from the program's `EscrowConfig`; a `referrer` wallet gets its
+/// share of the fee in its existing `mint_b` account. Pool-priced offers pass
+/// their pool accounts.
+pub fn take(
+    taker: &Pubkey,
+    escrow: &Escrow,
+    token_program: &Pubkey,
+    fee_recipient: &Pubkey,
+    referrer: Option<&Pubkey>,
+    max_amount_b: u64,
+) -> Instruction {
+    let (address, _) = escrow_address(&escrow.maker, escrow.seed);
+    let pool = escrow.price.map(|price| price.pool);
+
+    let accounts = accounts::Take {
+        taker: *taker,
+        maker: escrow.maker,
+        mint_a: escrow.mint_a,
+        mint_b: escrow.mint_b,
+        taker_ata_a: ata_address(taker, &escrow.mint_a, token_program),
+        taker_ata_b: ata_address(taker, &escrow.mint_b, token_program),
+        maker_ata_b: ata_address(&escrow.maker, &escrow.mint_b, token_program),
+        escrow: address,
+        vault: vault_address(&address, &escrow.mint_a, token_program),
+        escrow_config: config_address().0,
+        fee_recipient: *fee_recipient,
+        fee_recipient_ata_b: ata_address(fee_recipient, &escrow.mint_b, token_program),
+        referrer_ata_b: referrer
+            .map(|referrer| ata_address(referrer, &escrow.mint_b, token_program)),
+        pool,
+        // anchor_amm vaults are classic token accounts.
+        pool_vault_a: pool.map(|pool| get_associated_token_address(&pool, &escrow.mint_a)),
+        pool_vault_b: pool.map(|pool| get_associated_token_address(&pool, &escrow.mint_b)),
+        associated_token_program: associated_token::ID,
+        token_program: *token_program,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: anchor_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::Take { max_amount_b }.data(),
+    }
+}
+
+/// Refunds `escrow` to its maker.
+pub fn refund(escrow: &Escrow, token_program: &Pubkey) -> Instruction {
+    let (address, _) = escrow_address(&escrow.maker, escrow.seed);
+
+    let accounts = accounts::Refund {
+        maker: escrow.maker,
+        mint_a: escrow.mint_a,
+        maker_ata_a: ata_address(&escrow.maker, &escrow.mint_a, token_program),
+        escrow: address,
+        vault: vault_address(&address, &escrow.mint_a, token_program),
+        associated_token_program: associated_token::ID,
+        token_program: *token_program,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: anchor_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: instruction::Refund {}.data(),
+    }
+}
@@ -0,0 +1,11 @@
+//! Client for the `anchor_escrow` program: derives its PDAs, builds `make`,
+//! `take` and `refund` instructions, and finds open escrows by mint pair,
+//! maker, price, expiry and designated taker.
+
+pub mod instructions;
+pub mod pda;
+pub mod query;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+pub use anchor_escrow::{events, Escrow, EscrowConfig, PoolPrice, ID};
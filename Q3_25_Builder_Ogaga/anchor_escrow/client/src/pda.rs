@@ -0,0 +1,50 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+/// The escrow account for `maker`'s offer `seed`.
+pub fn escrow_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+        &anchor_escrow::ID,
+    )
+}
+
+/// `owner`'s associated token account for `mint`, e.g. a maker's, taker's or
+/// the fee recipient's.
+pub fn ata_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// The vault holding an escrow's `mint_a` deposit.
+pub fn vault_address(escrow: &Pubkey, mint_a: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    ata_address(escrow, mint_a, token_program)
+}
+
+/// The program-wide fee config.
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &anchor_escrow::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escrow_address_matches_bump() {
+        let maker = Pubkey::new_unique();
+        let (address, bump) = escrow_address(&maker, 42);
+
+        let derived = Pubkey::create_program_address(
+            &[b"escrow", maker.as_ref(), &42u64.to_le_bytes(), &[bump]],
+            &anchor_escrow::ID,
+        )
+        .unwrap();
+        assert_eq!(address, derived);
+    }
+
+    #[test]
+    fn escrows_differ_by_seed() {
+        let maker = Pubkey::new_unique();
+        assert_ne!(escrow_address(&maker, 1).0, escrow_address(&maker, 2).0);
+    }
+}
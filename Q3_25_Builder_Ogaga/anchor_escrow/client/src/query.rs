@@ -0,0 +1,207 @@
+use anchor_lang::{prelude::Pubkey, Discriminator};
+
+use anchor_escrow::Escrow;
+
+// Byte offsets of the fixed-position `Escrow` fields, after the 8-byte
+// account discriminator.
+const MAKER_OFFSET: usize = 8 + 8;
+const MINT_A_OFFSET: usize = MAKER_OFFSET + 32;
+const MINT_B_OFFSET: usize = MINT_A_OFFSET + 32;
+
+/// Narrows a search for open escrows. Maker and mints are matched by the RPC
+/// node; the price range, in `mint_b` per `mint_a` (raw units, using
+/// `receive` for pool-priced offers), expiry and designated taker are checked
+/// client-side.
+#[derive(Clone, Copy, Default)]
+pub struct EscrowFilter {
+    pub maker: Option<Pubkey>,
+    pub mint_a: Option<Pubkey>,
+    pub mint_b: Option<Pubkey>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// Drops offers that have expired by this unix timestamp.
+    pub now: Option<i64>,
+    /// Drops offers reserved for a taker other than this one.
+    pub taker: Option<Pubkey>,
+}
+
+impl EscrowFilter {
+    /// Offers of `mint_a` for `mint_b`.
+    pub fn pair(mint_a: Pubkey, mint_b: Pubkey) -> Self {
+        Self {
+            mint_a: Some(mint_a),
+            mint_b: Some(mint_b),
+            ..Self::default()
+        }
+    }
+
+    pub fn maker(self, maker: Pubkey) -> Self {
+        Self {
+            maker: Some(maker),
+            ..self
+        }
+    }
+
+    pub fn price_range(self, min_price: Option<f64>, max_price: Option<f64>) -> Self {
+        Self {
+            min_price,
+            max_price,
+            ..self
+        }
+    }
+
+    /// Only offers still open at `now`.
+    pub fn unexpired_at(self, now: i64) -> Self {
+        Self {
+            now: Some(now),
+            ..self
+        }
+    }
+
+    /// Only offers `taker` is allowed to take.
+    pub fn takeable_by(self, taker: Pubkey) -> Self {
+        Self {
+            taker: Some(taker),
+            ..self
+        }
+    }
+
+    /// `(offset, bytes)` pairs an escrow account's data must contain, always
+    /// starting with the `Escrow` discriminator.
+    pub fn memcmp(&self) -> Vec<(usize, Vec<u8>)> {
+        let mut filters = vec![(0, Escrow::DISCRIMINATOR.to_vec())];
+
+        let fields = [
+            (MAKER_OFFSET, self.maker),
+            (MINT_A_OFFSET, self.mint_a),
+            (MINT_B_OFFSET, self.mint_b),
+        ];
+        for (offset, key) in fields {
+            if let Some(key) = key {
+                filters.push((offset, key.to_bytes().to_vec()));
+            }
+        }
+
+        filters
+    }
+
+    pub fn matches(&self, escrow: &Escrow) -> bool {
+        let maker = self.maker.is_none_or(|maker| maker == escrow.maker);
+        let mint_a = self.mint_a.is_none_or(|mint| mint == escrow.mint_a);
+        let mint_b = self.mint_b.is_none_or(|mint| mint == escrow.mint_b);
+
+        let price = price(escrow);
+        let min = self.min_price.is_none_or(|min| price >= min);
+        let max = self.max_price.is_none_or(|max| price <= max);
+
+        let open = self.now.is_none_or(|now| !escrow.is_expired(now));
+        let taker = self.taker.is_none_or(|taker| escrow.can_be_taken_by(&taker));
+
+        maker && mint_a && mint_b && min && max && open && taker
+    }
+}
+
+/// What an escrow asks for each unit of `mint_a`, in raw `mint_b` units.
+pub fn price(escrow: &Escrow) -> f64 {
+    escrow.receive as f64 / escrow.deposit as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    fn escrow(maker: Pubkey, mint_a: Pubkey, mint_b: Pubkey, deposit: u64, receive: u64) -> Escrow {
+        Escrow {
+            seed: 7,
+            maker,
+            mint_a,
+            mint_b,
+            deposit,
+            receive,
+            expires_at: 0,
+            cleanup_reward: 0,
+            taker: None,
+            price: None,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn memcmp_offsets_match_the_account_layout() {
+        let (maker, mint_a, mint_b) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut data = Vec::new();
+        escrow(maker, mint_a, mint_b, 1, 1)
+            .try_serialize(&mut data)
+            .unwrap();
+
+        let filter = EscrowFilter::pair(mint_a, mint_b).maker(maker);
+        let filters = filter.memcmp();
+        assert_eq!(filters.len(), 4);
+        for (offset, bytes) in filters {
+            assert_eq!(&data[offset..offset + bytes.len()], bytes.as_slice());
+        }
+    }
+
+    #[test]
+    fn matches_by_pair_maker_and_price() {
+        let (maker, mint_a, mint_b) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let offer = escrow(maker, mint_a, mint_b, 100, 250);
+
+        assert!(EscrowFilter::default().matches(&offer));
+        assert!(EscrowFilter::pair(mint_a, mint_b)
+            .maker(maker)
+            .matches(&offer));
+        assert!(!EscrowFilter::pair(mint_b, mint_a).matches(&offer));
+        assert!(!EscrowFilter::default()
+            .maker(Pubkey::new_unique())
+            .matches(&offer));
+
+        assert!(EscrowFilter::default()
+            .price_range(Some(2.0), Some(3.0))
+            .matches(&offer));
+        assert!(!EscrowFilter::default()
+            .price_range(Some(3.0), None)
+            .matches(&offer));
+        assert!(!EscrowFilter::default()
+            .price_range(None, Some(2.0))
+            .matches(&offer));
+    }
+
+    #[test]
+    fn matches_by_expiry_and_designated_taker() {
+        let taker = Pubkey::new_unique();
+        let mut offer = escrow(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            100,
+            250,
+        );
+        offer.expires_at = 1_000;
+        offer.taker = Some(taker);
+
+        assert!(EscrowFilter::default().unexpired_at(999).matches(&offer));
+        assert!(!EscrowFilter::default().unexpired_at(1_000).matches(&offer));
+        assert!(EscrowFilter::default().takeable_by(taker).matches(&offer));
+        assert!(!EscrowFilter::default()
+            .takeable_by(Pubkey::new_unique())
+            .matches(&offer));
+
+        // Offers without an expiry or a designated taker are open to anyone.
+        offer.expires_at = 0;
+        offer.taker = None;
+        assert!(EscrowFilter::default()
+            .unexpired_at(i64::MAX)
+            .takeable_by(Pubkey::new_unique())
+            .matches(&offer));
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+
+use anchor_escrow::Escrow;
+
+use crate::query::EscrowFilter;
+
+/// Open escrows matching `filter`, e.g. against a local validator at
+/// `http://127.0.0.1:8899`. Accounts that fail to decode are skipped.
+pub fn fetch_escrows(
+    client: &RpcClient,
+    filter: &EscrowFilter,
+) -> Result<Vec<(Pubkey, Escrow)>, ClientError> {
+    let filters = filter
+        .memcmp()
+        .into_iter()
+        .map(|(offset, bytes)| RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, bytes)))
+        .collect();
+
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let accounts = client.get_program_accounts_with_config(&anchor_escrow::ID, config)?;
+
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            let escrow = Escrow::try_deserialize(&mut account.data.as_slice()).ok()?;
+            filter.matches(&escrow).then_some((address, escrow))
+        })
+        .collect())
+}
+
+/// The escrow at `address`, if it is still open.
+pub fn fetch_escrow(client: &RpcClient, address: &Pubkey) -> Result<Option<Escrow>, ClientError> {
+    let account = client
+        .get_account_with_commitment(address, client.commitment())?
+        .value;
+
+    Ok(account.and_then(|account| Escrow::try_deserialize(&mut account.data.as_slice()).ok()))
+}
@@ -0,0 +1,140 @@
+//! Runs the `rpc` helpers against a local validator with anchor_escrow
+//! deployed, e.g. one started by `anchor localnet`:
+//!
+//!     cargo test -p anchor-escrow-client --features rpc -- --ignored
+#![cfg(feature = "rpc")]
+
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::{
+    associated_token::spl_associated_token_account::instruction::create_associated_token_account,
+    token::spl_token,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::instruction::create_account;
+
+use anchor_escrow_client::{
+    instructions::{make, refund, MakeArgs},
+    pda::{ata_address, escrow_address},
+    query::EscrowFilter,
+    rpc::{fetch_escrow, fetch_escrows},
+};
+
+const URL: &str = "http://127.0.0.1:8899";
+
+fn send(client: &RpcClient, payer: &Keypair, instructions: &[Instruction], signers: &[&Keypair]) {
+    let blockhash = client.get_latest_blockhash().unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    client.send_and_confirm_transaction(&tx).unwrap();
+}
+
+// A funded maker holding 1_000 of a fresh `mint_a`, and a fresh `mint_b`.
+fn setup(client: &RpcClient) -> (Keypair, Keypair, Keypair) {
+    let maker = Keypair::new();
+    let signature = client
+        .request_airdrop(&maker.pubkey(), LAMPORTS_PER_SOL)
+        .unwrap();
+    client.poll_for_signature(&signature).unwrap();
+
+    let (mint_a, mint_b) = (Keypair::new(), Keypair::new());
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)
+        .unwrap();
+
+    let mut instructions = Vec::new();
+    for mint in [&mint_a, &mint_b] {
+        instructions.push(create_account(
+            &maker.pubkey(),
+            &mint.pubkey(),
+            rent,
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ));
+        instructions.push(
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &maker.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        );
+    }
+    instructions.push(create_associated_token_account(
+        &maker.pubkey(),
+        &maker.pubkey(),
+        &mint_a.pubkey(),
+        &spl_token::ID,
+    ));
+    instructions.push(
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint_a.pubkey(),
+            &ata_address(&maker.pubkey(), &mint_a.pubkey(), &spl_token::ID),
+            &maker.pubkey(),
+            &[],
+            1_000,
+        )
+        .unwrap(),
+    );
+    send(client, &maker, &instructions, &[&mint_a, &mint_b]);
+
+    (maker, mint_a, mint_b)
+}
+
+#[test]
+#[ignore = "needs a local validator with anchor_escrow deployed"]
+fn finds_open_escrows_until_refunded() {
+    let client = RpcClient::new_with_commitment(URL.to_string(), CommitmentConfig::confirmed());
+    let (maker, mint_a, mint_b) = setup(&client);
+
+    let args = MakeArgs {
+        seed: 7,
+        deposit: 100,
+        receive: 250,
+        ..MakeArgs::default()
+    };
+    let ix = make(
+        &maker.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        &spl_token::ID,
+        args,
+    );
+    send(&client, &maker, &[ix], &[]);
+
+    let (address, _) = escrow_address(&maker.pubkey(), args.seed);
+    let filter = EscrowFilter::pair(mint_a.pubkey(), mint_b.pubkey()).maker(maker.pubkey());
+
+    let escrows = fetch_escrows(&client, &filter).unwrap();
+    assert_eq!(escrows.len(), 1);
+    let (found, escrow) = &escrows[0];
+    assert_eq!(*found, address);
+    assert_eq!((escrow.deposit, escrow.receive), (100, 250));
+
+    // The reverse pair and a price above the offer's find nothing.
+    let reverse = EscrowFilter::pair(mint_b.pubkey(), mint_a.pubkey());
+    assert!(fetch_escrows(&client, &reverse).unwrap().is_empty());
+    let pricier = filter.price_range(Some(3.0), None);
+    assert!(fetch_escrows(&client, &pricier).unwrap().is_empty());
+
+    let escrow = fetch_escrow(&client, &address).unwrap().unwrap();
+    send(&client, &maker, &[refund(&escrow, &spl_token::ID)], &[]);
+
+    assert!(fetch_escrow(&client, &address).unwrap().is_none());
+    assert!(fetch_escrows(&client, &filter).unwrap().is_empty());
+}
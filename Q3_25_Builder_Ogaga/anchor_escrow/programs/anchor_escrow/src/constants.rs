@@ -2,3 +2,14 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Most mints a basket escrow can offer, and separately request.
+#[constant]
+pub const MAX_BASKET_LEGS: usize = 4;
+
+// Fees are in basis points of the taker's payment; the protocol fee is capped
+// at MAX_FEE_BPS.
+#[constant]
+pub const BPS_DENOMINATOR: u16 = 10_000;
+#[constant]
+pub const MAX_FEE_BPS: u16 = 1_000;
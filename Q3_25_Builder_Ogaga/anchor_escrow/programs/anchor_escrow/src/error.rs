@@ -2,6 +2,48 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Offer is reserved for a different taker")]
+    InvalidTaker,
+    #[msg("Basket legs are invalid")]
+    InvalidBasket,
+    #[msg("Fee is out of range")]
+    InvalidFee,
+    #[msg("Signer is not the config admin")]
+    InvalidAdmin,
+    #[msg("Deposit must be greater than zero")]
+    InvalidDeposit,
+    #[msg("Receive amount must be greater than zero")]
+    InvalidReceive,
+    #[msg("mint_a and mint_b must be different")]
+    IdenticalMints,
+    #[msg("Vault balance does not cover the escrowed deposit")]
+    VaultMismatch,
+    #[msg("Vesting schedule must satisfy start <= cliff <= end and start < end")]
+    InvalidSchedule,
+    #[msg("Nothing has vested since the last claim")]
+    NothingToClaim,
+    #[msg("This vesting escrow can't be cancelled")]
+    NotCancellable,
+    #[msg("Premium or max deviation is out of range")]
+    InvalidPricing,
+    #[msg("Pool accounts don't match the offer's price source")]
+    InvalidPriceSource,
+    #[msg("Pool price is too far from the offer's reference price")]
+    PriceDeviation,
+    #[msg("Pool-priced offers can only be taken in full")]
+    PricedPartialFill,
+    #[msg("Offer terms are worse than the taker's limit")]
+    SlippageExceeded,
+    #[msg("Pool is locked, sunset or closed")]
+    PoolUnavailable,
+    #[msg("Referrer can't be the taker or the maker")]
+    InvalidReferrer,
+    #[msg("Arithmetic overflow or underflow")]
+    MathOverflow,
 }
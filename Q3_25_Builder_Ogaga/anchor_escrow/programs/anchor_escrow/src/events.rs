@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+// Emitted on every change to an escrow's lifecycle so clients can follow open
+// offers from transaction logs instead of scanning program accounts.
+
+#[event]
+pub struct EscrowMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: i64,
+    pub taker: Option<Pubkey>,
+}
+
+#[event]
+pub struct EscrowUpdated {
+    pub escrow: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount_a: u64, // Released from the vault
+    pub amount_b: u64, // Paid by the taker, fees included
+    pub remaining: u64, // mint_a left in the escrow; 0 once it's closed
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub amount_a: u64,
+}
+
+#[event]
+pub struct EscrowExpired {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub caller: Pubkey,
+    pub amount_a: u64, // Returned to the maker
+    pub reward: u64,   // Cleanup reward paid to the caller
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Leg, MAX_BASKET_LEGS};
+
+// Helpers shared by the basket escrow instructions. Every leg is passed as a
+// group of three remaining accounts, each checked against the leg's mint.
+pub const ACCOUNTS_PER_LEG: usize = 3;
+
+pub fn check_legs(legs: &[Leg]) -> Result<()> {
+    require!(
+        !legs.is_empty() && legs.len() <= MAX_BASKET_LEGS,
+        ErrorCode::InvalidBasket
+    );
+
+    for (i, leg) in legs.iter().enumerate() {
+        require!(leg.amount > 0, ErrorCode::InvalidAmount);
+        // One vault per mint, so a mint can only appear once per side.
+        require!(
+            legs[..i].iter().all(|other| other.mint != leg.mint),
+            ErrorCode::InvalidBasket
+        );
+    }
+
+    Ok(())
+}
+
+// Checks `mint` is the leg's mint and returns its decimals.
+pub fn leg_decimals<'info>(leg: &Leg, mint: &'info AccountInfo<'info>, token_program: &Pubkey) -> Result<u8> {
+    require_keys_eq!(mint.key(), leg.mint, ErrorCode::InvalidBasket);
+    require_keys_eq!(*mint.owner, *token_program, ErrorCode::InvalidBasket);
+
+    Ok(InterfaceAccount::<Mint>::try_from(mint)?.decimals)
+}
+
+pub fn check_ata(account: &AccountInfo, wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        account.key(),
+        get_associated_token_address_with_program_id(wallet, mint, token_program),
+        ErrorCode::InvalidBasket
+    );
+
+    Ok(())
+}
+
+pub fn token_amount<'info>(account: &'info AccountInfo<'info>) -> Result<u64> {
+    Ok(InterfaceAccount::<TokenAccount>::try_from(account)?.amount)
+}
+
+pub fn create_ata<'info>(
+    associated_token_program: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    associated_token: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let accounts = Create {
+        payer,
+        associated_token,
+        authority,
+        mint,
+        system_program,
+        token_program,
+    };
+
+    create_idempotent(CpiContext::new(associated_token_program, accounts))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_leg<'info>(
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let accounts = TransferChecked {
+        from,
+        mint,
+        to,
+        authority,
+    };
+
+    let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    transfer_checked(ctx, amount, decimals)
+}
+
+pub fn close_vault<'info>(
+    token_program: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let accounts = CloseAccount {
+        account: vault,
+        destination,
+        authority,
+    };
+
+    let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    close_account(ctx)
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Vesting};
+
+#[derive(Accounts)]
+pub struct CancelVesting<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub beneficiary: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    pub beneficiary_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = beneficiary,
+        has_one = mint,
+        seeds = [b"vesting", maker.key().as_ref(), vesting.seed.to_le_bytes().as_ref()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelVesting<'info> {
+    // What has vested stays the beneficiary's and is paid out now; the
+    // unvested remainder goes back to the maker.
+    pub fn cancel_and_close_vault(&mut self) -> Result<()> {
+        require!(self.vesting.cancellable, ErrorCode::NotCancellable);
+
+        let vested = self.vesting.claimable(Clock::get()?.unix_timestamp);
+        let unvested = self
+            .vault
+            .amount
+            .checked_sub(vested)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.release(self.beneficiary_ata.to_account_info(), vested)?;
+        self.release(self.maker_ata.to_account_info(), unvested)?;
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"vesting",
+            self.maker.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes()[..],
+            &[self.vesting.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            &signer_seeds,
+        );
+
+        close_account(ctx)
+    }
+
+    fn release(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"vesting",
+            self.maker.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes()[..],
+            &[self.vesting.bump],
+        ]];
+
+        let xfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to,
+            authority: self.vesting.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            xfer_accounts,
+            &signer_seeds,
+        );
+
+        transfer_checked(ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Vesting};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    pub beneficiary_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = beneficiary,
+        has_one = mint,
+        seeds = [b"vesting", maker.key().as_ref(), vesting.seed.to_le_bytes().as_ref()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimVested<'info> {
+    // Pays out everything vested so far. The final claim empties the vault,
+    // including anything sent to it on top of the schedule, then closes it
+    // and the vesting account, returning their rent to the maker.
+    pub fn claim_vested(&mut self) -> Result<()> {
+        let amount = self.vesting.claimable(Clock::get()?.unix_timestamp);
+        require!(amount > 0, ErrorCode::NothingToClaim);
+
+        let claimed = self
+            .vesting
+            .claimed
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let final_claim = claimed >= self.vesting.total;
+        let payout = match final_claim {
+            true => self.vault.amount,
+            false => amount,
+        };
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"vesting",
+            self.maker.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes()[..],
+            &[self.vesting.bump],
+        ]];
+
+        let xfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.beneficiary_ata.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            xfer_accounts,
+            &signer_seeds,
+        );
+
+        transfer_checked(ctx, payout, self.mint.decimals)?;
+
+        self.vesting.claimed = claimed;
+        if !final_claim {
+            return Ok(());
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            &signer_seeds,
+        );
+
+        close_account(ctx)?;
+
+        self.vesting.close(self.maker.to_account_info())
+    }
+}
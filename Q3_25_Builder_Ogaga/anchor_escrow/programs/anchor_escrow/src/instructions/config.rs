@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, program::AnchorEscrow, EscrowConfig, BPS_DENOMINATOR, MAX_FEE_BPS};
+
+// There is a single config for the whole program and it can never be
+// re-created, so only the program's upgrade authority may initialize it and
+// become its first admin. Takes read the protocol fee from it and fail until
+// it exists, so initialize it right after deploying (with a zero fee for a
+// fee-free deployment).
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidAdmin)]
+    pub program: Program<'info, AnchorEscrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::InvalidAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EscrowConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub escrow_config: Account<'info, EscrowConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init(
+        &mut self,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        referral_bps: u16,
+        bumps: &InitializeConfigBumps,
+    ) -> Result<()> {
+        check_fees(fee_bps, referral_bps)?;
+
+        self.escrow_config.set_inner(EscrowConfig {
+            admin: self.admin.key(),
+            fee_bps,
+            fee_recipient,
+            referral_bps,
+            bump: bumps.escrow_config,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+        seeds = [b"config"],
+        bump = escrow_config.bump
+    )]
+    pub escrow_config: Account<'info, EscrowConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update(
+        &mut self,
+        admin: Pubkey,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        referral_bps: u16,
+    ) -> Result<()> {
+        check_fees(fee_bps, referral_bps)?;
+
+        self.escrow_config.admin = admin;
+        self.escrow_config.fee_bps = fee_bps;
+        self.escrow_config.fee_recipient = fee_recipient;
+        self.escrow_config.referral_bps = referral_bps;
+
+        Ok(())
+    }
+}
+
+fn check_fees(fee_bps: u16, referral_bps: u16) -> Result<()> {
+    require!(fee_bps <= MAX_FEE_BPS, ErrorCode::InvalidFee);
+    require!(referral_bps <= BPS_DENOMINATOR, ErrorCode::InvalidFee);
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, events::EscrowExpired, Escrow};
+
+// Anyone may clean up an expired offer. The deposit goes back to the maker
+// and the caller collects the cleanup reward the maker set aside at `make`.
+#[derive(Accounts)]
+pub struct Expire<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Expire<'info> {
+    pub fn expire_and_close_vault(&mut self) -> Result<()> {
+        require!(
+            self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::OfferNotExpired
+        );
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let xfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            xfer_accounts,
+            &signer_seeds,
+        );
+
+        transfer_checked(ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        emit!(EscrowExpired {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            caller: self.caller.key(),
+            amount_a: self.vault.amount,
+            reward: self.escrow.cleanup_reward,
+        });
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            &signer_seeds,
+        );
+
+        close_account(ctx)?;
+
+        // The rest of the escrow's lamports go back to the maker on close.
+        let reward = self.escrow.cleanup_reward;
+        let escrow = self.escrow.to_account_info();
+        let escrow_lamports = escrow
+            .lamports()
+            .checked_sub(reward)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let caller_lamports = self
+            .caller
+            .lamports()
+            .checked_add(reward)
+            .ok_or(ErrorCode::MathOverflow)?;
+        **escrow.try_borrow_mut_lamports()? = escrow_lamports;
+        **self.caller.to_account_info().try_borrow_mut_lamports()? = caller_lamports;
+
+        Ok(())
+    }
+}
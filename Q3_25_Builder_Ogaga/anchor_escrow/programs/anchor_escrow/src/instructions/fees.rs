@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{transfer_checked, Mint, TransferChecked};
+
+use crate::EscrowConfig;
+
+// Pays `amount` of mint_b from the taker, less the protocol fee, to the
+// maker. The fee goes to the fee recipient, minus the referrer's share when
+// a referrer account is passed.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_maker<'info>(
+    escrow_config: &EscrowConfig,
+    token_program: AccountInfo<'info>,
+    mint_b: &InterfaceAccount<'info, Mint>,
+    taker: AccountInfo<'info>,
+    taker_ata_b: AccountInfo<'info>,
+    maker_ata_b: AccountInfo<'info>,
+    fee_recipient_ata_b: AccountInfo<'info>,
+    referrer_ata_b: Option<AccountInfo<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let (to_maker, fee, referral) = escrow_config.split(amount, referrer_ata_b.is_some());
+
+    let legs = [
+        Some((maker_ata_b, to_maker)),
+        Some((fee_recipient_ata_b, fee)),
+        referrer_ata_b.map(|referrer_ata_b| (referrer_ata_b, referral)),
+    ];
+
+    for (to, amount) in legs.into_iter().flatten() {
+        if amount == 0 {
+            continue;
+        }
+
+        let transfer_accounts = TransferChecked {
+            from: taker_ata_b.clone(),
+            mint: mint_b.to_account_info(),
+            to,
+            authority: taker.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new(token_program.clone(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, mint_b.decimals)?;
+    }
+
+    Ok(())
+}
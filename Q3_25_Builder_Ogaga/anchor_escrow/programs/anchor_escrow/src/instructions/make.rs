@@ -1,11 +1,11 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::Escrow;
+use crate::{error::ErrorCode, events::EscrowMade, Escrow, PoolPrice};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -49,19 +49,73 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn save_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_escrow(
+        &mut self,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        cleanup_reward: u64,
+        taker: Option<Pubkey>,
+        price: Option<PoolPrice>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(receive > 0, ErrorCode::InvalidReceive);
+        require!(
+            price.is_none_or(|price| price.is_valid()),
+            ErrorCode::InvalidPricing
+        );
+        require!(
+            expires_at == 0 || expires_at > Clock::get()?.unix_timestamp,
+            ErrorCode::OfferExpired
+        );
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
+            deposit,
             receive,
+            expires_at,
+            cleanup_reward,
+            taker,
+            price,
             bump: bumps.escrow,
         });
+
+        emit!(EscrowMade {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            deposit,
+            receive,
+            expires_at,
+            taker,
+        });
+
+        // The reward sits in the escrow account on top of its rent until the
+        // offer is taken, refunded or expired.
+        if cleanup_reward > 0 {
+            let accounts = system_program::Transfer {
+                from: self.maker.to_account_info(),
+                to: self.escrow.to_account_info(),
+            };
+            system_program::transfer(
+                CpiContext::new(self.system_program.to_account_info(), accounts),
+                cleanup_reward,
+            )?;
+        }
+
         Ok(())
     }
 
     pub fn deposit(&mut self, deposit: u64) -> Result<()> {
+        require!(deposit > 0, ErrorCode::InvalidDeposit);
+        require_keys_neq!(self.mint_a.key(), self.mint_b.key(), ErrorCode::IdenticalMints);
+
         let transfer_accounts = TransferChecked {
             from: self.maker_ata_a.to_account_info(),
             mint: self.mint_a.to_account_info(),
@@ -71,6 +125,13 @@ impl<'info> Make<'info> {
 
         let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
 
-        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)
+        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)?;
+
+        // A Token-2022 transfer fee would leave the vault short of what the
+        // escrow promises takers.
+        self.vault.reload()?;
+        require!(self.vault.amount == deposit, ErrorCode::VaultMismatch);
+
+        Ok(())
     }
 }
\ No newline at end of file
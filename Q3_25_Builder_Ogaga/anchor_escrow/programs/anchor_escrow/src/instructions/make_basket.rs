@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use crate::{error::ErrorCode, BasketEscrow, Leg};
+
+use super::basket::{
+    check_ata, check_legs, create_ata, leg_decimals, transfer_leg, ACCOUNTS_PER_LEG,
+};
+
+// Remaining accounts: [(mint, maker_ata, vault) * offered]. Each vault is the
+// basket's associated token account for the mint and is created here.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + BasketEscrow::INIT_SPACE,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    pub fn save_basket(
+        &mut self,
+        seed: u64,
+        offered: Vec<Leg>,
+        requested: Vec<Leg>,
+        bumps: &MakeBasketBumps,
+    ) -> Result<()> {
+        check_legs(&offered)?;
+        check_legs(&requested)?;
+
+        self.basket.set_inner(BasketEscrow {
+            seed,
+            maker: self.maker.key(),
+            offered,
+            requested,
+            bump: bumps.basket,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            remaining_accounts.len() == self.basket.offered.len() * ACCOUNTS_PER_LEG,
+            ErrorCode::InvalidBasket
+        );
+
+        let token_program = self.token_program.key();
+        for (leg, accounts) in self
+            .basket
+            .offered
+            .iter()
+            .zip(remaining_accounts.chunks(ACCOUNTS_PER_LEG))
+        {
+            let (mint, maker_ata, vault) = (&accounts[0], &accounts[1], &accounts[2]);
+            let decimals = leg_decimals(leg, mint, &token_program)?;
+            check_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+            check_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+
+            create_ata(
+                self.associated_token_program.to_account_info(),
+                self.maker.to_account_info(),
+                vault.clone(),
+                self.basket.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+            )?;
+
+            transfer_leg(
+                self.token_program.to_account_info(),
+                maker_ata.clone(),
+                mint.clone(),
+                vault.clone(),
+                self.maker.to_account_info(),
+                leg.amount,
+                decimals,
+                &[],
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, Vesting};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeVesting<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub beneficiary: SystemAccount<'info>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Vesting::INIT_SPACE,
+        seeds = [b"vesting", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeVesting<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_vesting(
+        &mut self,
+        seed: u64,
+        total: u64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+        cancellable: bool,
+        bumps: &MakeVestingBumps,
+    ) -> Result<()> {
+        require!(
+            start <= cliff && cliff <= end && start < end,
+            ErrorCode::InvalidSchedule
+        );
+
+        self.vesting.set_inner(Vesting {
+            seed,
+            maker: self.maker.key(),
+            beneficiary: self.beneficiary.key(),
+            mint: self.mint.key(),
+            total,
+            claimed: 0,
+            start,
+            cliff,
+            end,
+            cancellable,
+            bump: bumps.vesting,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, total: u64) -> Result<()> {
+        require!(total > 0, ErrorCode::InvalidDeposit);
+
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, total, self.mint.decimals)?;
+
+        self.vault.reload()?;
+        require!(self.vault.amount == total, ErrorCode::VaultMismatch);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,29 @@
+mod basket;
+pub mod cancel_vesting;
+pub mod claim_vested;
+pub mod config;
+pub mod expire;
+mod fees;
 pub mod make;
+pub mod make_basket;
+pub mod make_vesting;
 pub mod refund;
+pub mod refund_basket;
 pub mod take;
+pub mod take_basket;
+pub mod take_partial;
+pub mod update;
 
+pub use cancel_vesting::*;
+pub use claim_vested::*;
+pub use config::*;
+pub use expire::*;
 pub use make::*;
+pub use make_basket::*;
+pub use make_vesting::*;
 pub use refund::*;
-pub use take::*;
\ No newline at end of file
+pub use refund_basket::*;
+pub use take::*;
+pub use take_basket::*;
+pub use take_partial::*;
+pub use update::*;
\ No newline at end of file
@@ -8,7 +8,7 @@ use anchor_spl::{
     },
 };
 
-use crate::Escrow;
+use crate::{events::EscrowRefunded, Escrow};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -67,6 +67,12 @@ impl<'info> Refund<'info> {
 
         transfer_checked(ctx, self.vault.amount, self.mint_a.decimals)?;
 
+        emit!(EscrowRefunded {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            amount_a: self.vault.amount,
+        });
+
         let close_accounts = CloseAccount {
             account: self.vault.to_account_info(),
             destination: self.maker.to_account_info(),
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use crate::{error::ErrorCode, BasketEscrow};
+
+use super::basket::{
+    check_ata, close_vault, create_ata, leg_decimals, token_amount, transfer_leg,
+    ACCOUNTS_PER_LEG,
+};
+
+// Remaining accounts: [(mint, vault, maker_ata) * offered].
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    maker: Signer<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    basket: Account<'info, BasketEscrow>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    token_program: Interface<'info, TokenInterface>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    pub fn refund_and_close_vaults(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            remaining_accounts.len() == self.basket.offered.len() * ACCOUNTS_PER_LEG,
+            ErrorCode::InvalidBasket
+        );
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"basket",
+            self.maker.to_account_info().key.as_ref(),
+            &self.basket.seed.to_le_bytes()[..],
+            &[self.basket.bump],
+        ]];
+
+        let token_program = self.token_program.key();
+        for (leg, accounts) in self
+            .basket
+            .offered
+            .iter()
+            .zip(remaining_accounts.chunks(ACCOUNTS_PER_LEG))
+        {
+            let (mint, vault, maker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+            let decimals = leg_decimals(leg, mint, &token_program)?;
+            check_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+            check_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+
+            create_ata(
+                self.associated_token_program.to_account_info(),
+                self.maker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+            )?;
+
+            transfer_leg(
+                self.token_program.to_account_info(),
+                vault.clone(),
+                mint.clone(),
+                maker_ata.clone(),
+                self.basket.to_account_info(),
+                token_amount(vault)?,
+                decimals,
+                &signer_seeds,
+            )?;
+
+            close_vault(
+                self.token_program.to_account_info(),
+                vault.clone(),
+                self.maker.to_account_info(),
+                self.basket.to_account_info(),
+                &signer_seeds,
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,18 @@
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address, AssociatedToken},
     token_interface::{
         close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
         TransferChecked,
     },
 };
 
-use crate::Escrow;
+use anchor_amm::state::Config as PoolConfig;
+
+use crate::{error::ErrorCode, events::EscrowTaken, Escrow, EscrowConfig};
+
+use super::fees::pay_maker;
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -58,23 +62,133 @@ pub struct Take<'info> {
         associated_token::token_program = token_program,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    // Required, so a taker can't skip the protocol fee by leaving it out.
+    #[account(
+        seeds = [b"config"],
+        bump = escrow_config.bump
+    )]
+    pub escrow_config: Box<Account<'info, EscrowConfig>>,
+    /// CHECK: Only used as the owner of the fee recipient's token account.
+    #[account(address = escrow_config.fee_recipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub fee_recipient_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    // The referral share comes out of the protocol fee, so neither side of
+    // the trade may claim it.
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program,
+        constraint = referrer_ata_b.owner != taker.key()
+            && referrer_ata_b.owner != maker.key() @ ErrorCode::InvalidReferrer,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Only needed for offers priced off an anchor_amm pool.
+    pub pool: Option<Box<Account<'info, PoolConfig>>>,
+    pub pool_vault_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub pool_vault_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Take<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
-        let transfer_accounts = TransferChecked {
-            from: self.taker_ata_b.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-            to: self.maker_ata_b.to_account_info(),
-            authority: self.taker.to_account_info(),
+    // The maker can reprice an open offer and a pool price can move, so the
+    // taker caps what they are willing to pay with `max_amount_b`.
+    pub fn deposit(&mut self, max_amount_b: u64) -> Result<()> {
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::OfferExpired
+        );
+        require!(
+            self.escrow.can_be_taken_by(&self.taker.key()),
+            ErrorCode::InvalidTaker
+        );
+        require!(
+            self.vault.amount >= self.escrow.deposit,
+            ErrorCode::VaultMismatch
+        );
+
+        let amount_b = self.amount_owed()?;
+        require!(amount_b <= max_amount_b, ErrorCode::SlippageExceeded);
+
+        emit!(EscrowTaken {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount_a: self.vault.amount,
+            amount_b,
+            remaining: 0,
+        });
+
+        pay_maker(
+            &self.escrow_config,
+            self.token_program.to_account_info(),
+            &self.mint_b,
+            self.taker.to_account_info(),
+            self.taker_ata_b.to_account_info(),
+            self.maker_ata_b.to_account_info(),
+            self.fee_recipient_ata_b.to_account_info(),
+            self.referrer_ata_b.as_ref().map(|referrer_ata_b| referrer_ata_b.to_account_info()),
+            amount_b,
+        )
+    }
+
+    // What the taker pays: the fixed `receive`, or for a pool-priced offer
+    // the deposit valued at the pool's current price.
+    fn amount_owed(&self) -> Result<u64> {
+        let Some(price) = self.escrow.price else {
+            return Ok(self.escrow.receive);
         };
 
-        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+        let (Some(pool), Some(pool_vault_a), Some(pool_vault_b)) =
+            (&self.pool, &self.pool_vault_a, &self.pool_vault_b)
+        else {
+            return err!(ErrorCode::InvalidPriceSource);
+        };
+        require_keys_eq!(pool.key(), price.pool, ErrorCode::InvalidPriceSource);
+        require!(
+            (pool.mint_x, pool.mint_y) == (self.mint_a.key(), self.mint_b.key())
+                || (pool.mint_x, pool.mint_y) == (self.mint_b.key(), self.mint_a.key()),
+            ErrorCode::InvalidPriceSource
+        );
+        require_keys_eq!(
+            pool_vault_a.key(),
+            get_associated_token_address(&pool.key(), &self.mint_a.key()),
+            ErrorCode::InvalidPriceSource
+        );
+        require_keys_eq!(
+            pool_vault_b.key(),
+            get_associated_token_address(&pool.key(), &self.mint_b.key()),
+            ErrorCode::InvalidPriceSource
+        );
+        // A locked pool's reserves may be mid-flash-loan or otherwise in flux,
+        // and a retired pool's price is no longer maintained.
+        require!(
+            !pool.is_locked() && !pool.sunset && !pool.closed,
+            ErrorCode::PoolUnavailable
+        );
+
+        let owed = price
+            .amount_owed(
+                pool.quote_curve(Clock::get()?.unix_timestamp),
+                pool_vault_a.amount,
+                pool_vault_b.amount,
+                self.escrow.deposit,
+            )
+            .ok_or(ErrorCode::InvalidPriceSource)?;
+        require!(owed > 0, ErrorCode::InvalidPriceSource);
+        require!(
+            price.within_deviation(owed, self.escrow.receive),
+            ErrorCode::PriceDeviation
+        );
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        Ok(owed)
     }
 
     pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
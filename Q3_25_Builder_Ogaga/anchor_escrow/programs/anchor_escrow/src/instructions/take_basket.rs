@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use crate::{error::ErrorCode, BasketEscrow};
+
+use super::basket::{
+    check_ata, close_vault, create_ata, leg_decimals, token_amount, transfer_leg,
+    ACCOUNTS_PER_LEG,
+};
+
+// Remaining accounts: [(mint, vault, taker_ata) * offered] followed by
+// [(mint, taker_ata, maker_ata) * requested]. Every leg settles in this one
+// instruction, so either the whole basket trades or nothing does.
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), basket.seed.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    basket: Account<'info, BasketEscrow>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBasket<'info> {
+    pub fn take_basket(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let offered = self.basket.offered.len() * ACCOUNTS_PER_LEG;
+        let requested = self.basket.requested.len() * ACCOUNTS_PER_LEG;
+        require!(
+            remaining_accounts.len() == offered + requested,
+            ErrorCode::InvalidBasket
+        );
+
+        let (offered_accounts, requested_accounts) = remaining_accounts.split_at(offered);
+        self.deposit(requested_accounts)?;
+        self.withdraw_and_close_vaults(offered_accounts)
+    }
+
+    fn deposit(&self, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let token_program = self.token_program.key();
+        for (leg, accounts) in self
+            .basket
+            .requested
+            .iter()
+            .zip(accounts.chunks(ACCOUNTS_PER_LEG))
+        {
+            let (mint, taker_ata, maker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+            let decimals = leg_decimals(leg, mint, &token_program)?;
+            check_ata(taker_ata, &self.taker.key(), &leg.mint, &token_program)?;
+            check_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+
+            create_ata(
+                self.associated_token_program.to_account_info(),
+                self.taker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+            )?;
+
+            transfer_leg(
+                self.token_program.to_account_info(),
+                taker_ata.clone(),
+                mint.clone(),
+                maker_ata.clone(),
+                self.taker.to_account_info(),
+                leg.amount,
+                decimals,
+                &[],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn withdraw_and_close_vaults(&self, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"basket",
+            self.maker.to_account_info().key.as_ref(),
+            &self.basket.seed.to_le_bytes()[..],
+            &[self.basket.bump],
+        ]];
+
+        let token_program = self.token_program.key();
+        for (leg, accounts) in self
+            .basket
+            .offered
+            .iter()
+            .zip(accounts.chunks(ACCOUNTS_PER_LEG))
+        {
+            let (mint, vault, taker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+            let decimals = leg_decimals(leg, mint, &token_program)?;
+            check_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+            check_ata(taker_ata, &self.taker.key(), &leg.mint, &token_program)?;
+
+            create_ata(
+                self.associated_token_program.to_account_info(),
+                self.taker.to_account_info(),
+                taker_ata.clone(),
+                self.taker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+            )?;
+
+            transfer_leg(
+                self.token_program.to_account_info(),
+                vault.clone(),
+                mint.clone(),
+                taker_ata.clone(),
+                self.basket.to_account_info(),
+                token_amount(vault)?,
+                decimals,
+                &signer_seeds,
+            )?;
+
+            close_vault(
+                self.token_program.to_account_info(),
+                vault.clone(),
+                self.taker.to_account_info(),
+                self.basket.to_account_info(),
+                &signer_seeds,
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, events::EscrowTaken, Escrow, EscrowConfig};
+
+use super::fees::pay_maker;
+
+#[derive(Accounts)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    // Required, so a taker can't skip the protocol fee by leaving it out.
+    #[account(
+        seeds = [b"config"],
+        bump = escrow_config.bump
+    )]
+    pub escrow_config: Box<Account<'info, EscrowConfig>>,
+    /// CHECK: Only used as the owner of the fee recipient's token account.
+    #[account(address = escrow_config.fee_recipient)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub fee_recipient_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    // The referral share comes out of the protocol fee, so neither side of
+    // the trade may claim it.
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program,
+        constraint = referrer_ata_b.owner != taker.key()
+            && referrer_ata_b.owner != maker.key() @ ErrorCode::InvalidReferrer,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakePartial<'info> {
+    // Pays `amount_b` of the remaining `receive` and releases the same share
+    // of the remaining deposit, rounded down in the maker's favour. Filling
+    // whatever is left closes the escrow. As the maker can reprice the offer,
+    // the taker sets the least `min_amount_a` they accept for `amount_b`.
+    pub fn take_partial(&mut self, amount_b: u64, min_amount_a: u64) -> Result<()> {
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::OfferExpired
+        );
+        require!(
+            self.escrow.can_be_taken_by(&self.taker.key()),
+            ErrorCode::InvalidTaker
+        );
+        require!(
+            self.vault.amount >= self.escrow.deposit,
+            ErrorCode::VaultMismatch
+        );
+        require!(self.escrow.price.is_none(), ErrorCode::PricedPartialFill);
+        require!(amount_b > 0, ErrorCode::InvalidAmount);
+        require!(amount_b <= self.escrow.receive, ErrorCode::InvalidAmount);
+
+        let filled = amount_b == self.escrow.receive;
+        let amount_a = match filled {
+            true => self.vault.amount,
+            false => (amount_b as u128 * self.escrow.deposit as u128 / self.escrow.receive as u128) as u64,
+        };
+        require!(amount_a > 0, ErrorCode::InvalidAmount);
+        require!(amount_a >= min_amount_a, ErrorCode::SlippageExceeded);
+
+        self.deposit(amount_b)?;
+        self.withdraw(amount_a)?;
+
+        emit!(EscrowTaken {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount_a,
+            amount_b,
+            remaining: self.escrow.deposit - amount_a,
+        });
+
+        if filled {
+            self.close_vault()?;
+            return self.escrow.close(self.maker.to_account_info());
+        }
+
+        self.escrow.deposit -= amount_a;
+        self.escrow.receive -= amount_b;
+
+        Ok(())
+    }
+
+    fn deposit(&mut self, amount: u64) -> Result<()> {
+        pay_maker(
+            &self.escrow_config,
+            self.token_program.to_account_info(),
+            &self.mint_b,
+            self.taker.to_account_info(),
+            self.taker_ata_b.to_account_info(),
+            self.maker_ata_b.to_account_info(),
+            self.fee_recipient_ata_b.to_account_info(),
+            self.referrer_ata_b.as_ref().map(|referrer_ata_b| referrer_ata_b.to_account_info()),
+            amount,
+        )
+    }
+
+    fn withdraw(&mut self, amount: u64) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            &signer_seeds,
+        );
+
+        transfer_checked(ctx, amount, self.mint_a.decimals)
+    }
+
+    fn close_vault(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            &signer_seeds,
+        );
+
+        close_account(ctx)
+    }
+}
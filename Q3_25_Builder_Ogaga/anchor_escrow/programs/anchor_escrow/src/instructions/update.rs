@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{error::ErrorCode, events::EscrowUpdated, Escrow};
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Update<'info> {
+    // Each field left as `None` keeps its current value. A new `deposit` is
+    // the total the vault should hold; the difference is topped up from or
+    // returned to the maker.
+    pub fn update_escrow(
+        &mut self,
+        receive: Option<u64>,
+        deposit: Option<u64>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        if let Some(receive) = receive {
+            require!(receive > 0, ErrorCode::InvalidReceive);
+            self.escrow.receive = receive;
+        }
+
+        if let Some(deposit) = deposit {
+            require!(deposit > 0, ErrorCode::InvalidDeposit);
+            let current = self.escrow.deposit;
+            if deposit > current {
+                self.top_up(deposit - current)?;
+            } else if deposit < current {
+                self.withdraw(current - deposit)?;
+            }
+            self.escrow.deposit = deposit;
+
+            self.vault.reload()?;
+            require!(self.vault.amount >= deposit, ErrorCode::VaultMismatch);
+        }
+
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at == 0 || expires_at > Clock::get()?.unix_timestamp,
+                ErrorCode::OfferExpired
+            );
+            self.escrow.expires_at = expires_at;
+        }
+
+        emit!(EscrowUpdated {
+            escrow: self.escrow.key(),
+            deposit: self.escrow.deposit,
+            receive: self.escrow.receive,
+            expires_at: self.escrow.expires_at,
+        });
+
+        Ok(())
+    }
+
+    fn top_up(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)
+    }
+
+    fn withdraw(&mut self, amount: u64) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let xfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            xfer_accounts,
+            &signer_seeds,
+        );
+
+        transfer_checked(ctx, amount, self.mint_a.decimals)
+    }
+}
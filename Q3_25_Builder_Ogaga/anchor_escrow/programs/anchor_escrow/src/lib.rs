@@ -2,6 +2,7 @@
 #![allow(deprecated)]
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
@@ -17,17 +18,113 @@ declare_id!("6XeXD5Te8cPbPwGJr3URk67c5mozyCWbg9aozDKBLE59");
 pub mod anchor_escrow {
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        referral_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.init(fee_bps, fee_recipient, referral_bps, &ctx.bumps)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        admin: Pubkey,
+        fee_bps: u16,
+        fee_recipient: Pubkey,
+        referral_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.update(admin, fee_bps, fee_recipient, referral_bps)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        cleanup_reward: u64,
+        taker: Option<Pubkey>,
+        price: Option<PoolPrice>,
+    ) -> Result<()> {
         ctx.accounts.deposit(deposit)?;
-        ctx.accounts.save_escrow(seed, receive, &ctx.bumps)
+        ctx.accounts.save_escrow(
+            seed,
+            deposit,
+            receive,
+            expires_at,
+            cleanup_reward,
+            taker,
+            price,
+            &ctx.bumps,
+        )
     }
 
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.deposit()?;
+    pub fn expire(ctx: Context<Expire>) -> Result<()> {
+        ctx.accounts.expire_and_close_vault()
+    }
+
+    pub fn take(ctx: Context<Take>, max_amount_b: u64) -> Result<()> {
+        ctx.accounts.deposit(max_amount_b)?;
         ctx.accounts.withdraw_and_close_vault()
     }
+
+    pub fn update_escrow(
+        ctx: Context<Update>,
+        receive: Option<u64>,
+        deposit: Option<u64>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_escrow(receive, deposit, expires_at)
+    }
+
+    pub fn make_vesting(
+        ctx: Context<MakeVesting>,
+        seed: u64,
+        total: u64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+        cancellable: bool,
+    ) -> Result<()> {
+        ctx.accounts.deposit(total)?;
+        ctx.accounts.save_vesting(seed, total, start, cliff, end, cancellable, &ctx.bumps)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        ctx.accounts.claim_vested()
+    }
+
+    pub fn cancel_vesting(ctx: Context<CancelVesting>) -> Result<()> {
+        ctx.accounts.cancel_and_close_vault()
+    }
+
+    pub fn make_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeBasket<'info>>,
+        seed: u64,
+        offered: Vec<Leg>,
+        requested: Vec<Leg>,
+    ) -> Result<()> {
+        ctx.accounts.save_basket(seed, offered, requested, &ctx.bumps)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBasket<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.refund_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    pub fn take_basket<'info>(ctx: Context<'_, '_, 'info, 'info, TakeBasket<'info>>) -> Result<()> {
+        ctx.accounts.take_basket(ctx.remaining_accounts)
+    }
+
+    pub fn take_partial(ctx: Context<TakePartial>, amount_b: u64, min_amount_a: u64) -> Result<()> {
+        ctx.accounts.take_partial(amount_b, min_amount_a)
+    }
 }
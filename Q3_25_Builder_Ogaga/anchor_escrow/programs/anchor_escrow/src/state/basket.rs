@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::MAX_BASKET_LEGS;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Leg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BasketEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub offered: Vec<Leg>, // Held in one vault per mint under the basket PDA
+    #[max_len(MAX_BASKET_LEGS)]
+    pub requested: Vec<Leg>, // Paid straight to the maker on take
+    pub bump: u8
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::BPS_DENOMINATOR;
+
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowConfig {
+    pub admin: Pubkey, // May change the fee settings
+    pub fee_bps: u16, // Cut of every take's mint_b payment
+    pub fee_recipient: Pubkey, // Wallet the protocol fee is paid to
+    pub referral_bps: u16, // Share of the fee paid to a referrer, if any
+    pub bump: u8
+}
+
+impl EscrowConfig {
+    /// Splits a taker's payment of `amount` into what the maker, the fee
+    /// recipient and the referrer receive. Fees round down, in the maker's
+    /// favour.
+    pub fn split(&self, amount: u64, has_referrer: bool) -> (u64, u64, u64) {
+        let fee = (amount as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let referral = match has_referrer {
+            true => (fee as u128 * self.referral_bps as u128 / BPS_DENOMINATOR as u128) as u64,
+            false => 0,
+        };
+
+        (amount - fee, fee - referral, referral)
+    }
+}
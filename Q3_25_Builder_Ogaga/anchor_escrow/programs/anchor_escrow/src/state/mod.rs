@@ -1,4 +1,15 @@
 use anchor_lang::prelude::*;
+use amm_quote::Curve;
+
+pub mod basket;
+pub mod escrow_config;
+pub mod vesting;
+
+pub use basket::*;
+pub use escrow_config::*;
+pub use vesting::*;
+
+use crate::BPS_DENOMINATOR;
 
 #[account]
 #[derive(InitSpace)]
@@ -7,6 +18,99 @@ pub struct Escrow {
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
-    pub receive: u64,
+    pub deposit: u64, // mint_a still held in the vault
+    pub receive: u64, // mint_b still owed for the rest of the deposit
+    pub expires_at: i64, // Can't be taken from this time on; 0 never expires
+    pub cleanup_reward: u64, // Lamports paid to whoever calls `expire`
+    pub taker: Option<Pubkey>, // Only this wallet may take the offer, if set
+    pub price: Option<PoolPrice>, // Prices the offer off an anchor_amm pool instead of `receive`
     pub bump: u8
-}
\ No newline at end of file
+}
+
+impl Escrow {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        self.taker.is_none_or(|designated| designated == *taker)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct PoolPrice {
+    pub pool: Pubkey, // anchor_amm pool config for the mint_a / mint_b pair
+    pub premium_bps: i16, // Added to the pool price, or taken off it if negative
+    pub max_deviation_bps: u16, // Furthest the priced amount may stray from `receive`
+}
+
+impl PoolPrice {
+    pub fn is_valid(&self) -> bool {
+        self.premium_bps.unsigned_abs() < BPS_DENOMINATOR
+            && self.max_deviation_bps <= BPS_DENOMINATOR
+    }
+
+    /// mint_b owed for `deposit` of mint_a at the pool's marginal price
+    /// (fees and price impact aside), adjusted by the premium.
+    pub fn amount_owed(&self, curve: Curve, reserve_a: u64, reserve_b: u64, deposit: u64) -> Option<u64> {
+        let at_pool_price = amm_quote::spot_amount_out(curve, reserve_a, reserve_b, deposit)?;
+        let adjusted = at_pool_price as i128 * (BPS_DENOMINATOR as i128 + self.premium_bps as i128)
+            / BPS_DENOMINATOR as i128;
+
+        u64::try_from(adjusted).ok()
+    }
+
+    /// Whether `owed` is within `max_deviation_bps` of the maker's reference
+    /// amount. Pool reserves can be pushed around within a transaction, so
+    /// this bounds what a manipulated price can cost the maker.
+    pub fn within_deviation(&self, owed: u64, reference: u64) -> bool {
+        (owed.abs_diff(reference) as u128) * BPS_DENOMINATOR as u128
+            <= reference as u128 * self.max_deviation_bps as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(premium_bps: i16, max_deviation_bps: u16) -> PoolPrice {
+        PoolPrice {
+            pool: Pubkey::new_unique(),
+            premium_bps,
+            max_deviation_bps,
+        }
+    }
+
+    #[test]
+    fn amount_owed_applies_the_premium_to_the_spot_price() {
+        let curve = Curve::ConstantProduct;
+
+        // Pool prices a unit of mint_a at 2 mint_b.
+        assert_eq!(price(0, 0).amount_owed(curve, 1_000, 2_000, 100), Some(200));
+        assert_eq!(price(100, 0).amount_owed(curve, 1_000, 2_000, 1_000_000), Some(2_020_000));
+        assert_eq!(price(-250, 0).amount_owed(curve, 1_000, 2_000, 1_000_000), Some(1_950_000));
+    }
+
+    #[test]
+    fn amount_owed_needs_reserves() {
+        assert_eq!(price(0, 0).amount_owed(Curve::ConstantProduct, 0, 2_000, 100), None);
+    }
+
+    #[test]
+    fn within_deviation_is_inclusive_on_both_sides() {
+        let price = price(0, 500);
+
+        assert!(price.within_deviation(1_000_000, 1_000_000));
+        assert!(price.within_deviation(1_050_000, 1_000_000));
+        assert!(price.within_deviation(950_000, 1_000_000));
+        assert!(!price.within_deviation(1_050_001, 1_000_000));
+        assert!(!price.within_deviation(949_999, 1_000_000));
+    }
+
+    #[test]
+    fn is_valid_bounds_premium_and_deviation() {
+        assert!(price(-9_999, 10_000).is_valid());
+        assert!(!price(-10_000, 0).is_valid());
+        assert!(!price(0, 10_001).is_valid());
+    }
+}
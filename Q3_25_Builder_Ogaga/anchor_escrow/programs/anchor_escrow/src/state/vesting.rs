@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub total: u64, // Deposited up front and released over the schedule
+    pub claimed: u64, // Already paid out to the beneficiary
+    pub start: i64, // Release starts accruing from here...
+    pub cliff: i64, // ...but nothing can be claimed before the cliff
+    pub end: i64, // Everything has vested from here on
+    pub cancellable: bool, // If the maker may take back the unvested remainder
+    pub bump: u8
+}
+
+impl Vesting {
+    /// Amount released by `now`: nothing before the cliff, then linear from
+    /// `start` to `end`.
+    pub fn vested(&self, now: i64) -> u64 {
+        if now < self.cliff {
+            return 0;
+        }
+        if now >= self.end {
+            return self.total;
+        }
+
+        let elapsed = (now - self.start) as u128;
+        let duration = (self.end - self.start) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.claimed)
+    }
+}
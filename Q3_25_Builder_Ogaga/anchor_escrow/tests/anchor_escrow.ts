@@ -1,6 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { AnchorEscrow } from "../target/types/anchor_escrow";
+import { AnchorAmm } from "../../anchor_amm/target/types/anchor_amm";
+import ammIdl from "../../anchor_amm/target/idl/anchor_amm.json";
+import { FlashReceiver } from "../../anchor_amm/target/types/flash_receiver";
+import receiverIdl from "../../anchor_amm/target/idl/flash_receiver.json";
 import {
   Keypair,
   LAMPORTS_PER_SOL,
@@ -15,10 +19,13 @@ import {
   createAssociatedTokenAccountIdempotentInstruction,
   createInitializeMint2Instruction,
   createMintToInstruction,
+  createTransferCheckedInstruction,
+  TOKEN_PROGRAM_ID,
   getAssociatedTokenAddressSync,
   getMinimumBalanceForRentExemptMint,
 } from "@solana/spl-token";
 import { randomBytes } from "crypto";
+import { assert } from "chai";
 
 describe("anchor-escrow", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -83,6 +90,21 @@ describe("anchor-escrow", () => {
     tokenProgram
   );
 
+  // Protocol fee of 1%, a fifth of which goes to a referrer when one is set
+  const [feeRecipient, referrer] = [Keypair.generate(), Keypair.generate()];
+  const feeRecipientAtaB = getAssociatedTokenAddressSync(
+    mintB.publicKey,
+    feeRecipient.publicKey,
+    false,
+    tokenProgram
+  );
+  const referrerAtaB = getAssociatedTokenAddressSync(
+    mintB.publicKey,
+    referrer.publicKey,
+    false,
+    tokenProgram
+  );
+
   // Accounts
   const accounts = {
     maker: maker.publicKey,
@@ -95,6 +117,8 @@ describe("anchor-escrow", () => {
     takerAtaB,
     escrow,
     vault,
+    feeRecipient: feeRecipient.publicKey,
+    feeRecipientAtaB,
     tokenProgram,
   };
 
@@ -150,9 +174,161 @@ describe("anchor-escrow", () => {
     await provider.sendAndConfirm(tx, [mintA, mintB, maker, taker]).then(log);
   });
 
+  // Only the upgrade authority (the local wallet on a test validator) may
+  // create the config.
+  const programData = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  it("Only the upgrade authority can initialize the config", async () => {
+    try {
+      await program.methods
+        .initializeConfig(1000, taker.publicKey, 0)
+        .accounts({ admin: taker.publicKey, programData })
+        .signers([taker])
+        .rpc();
+      assert.fail("initialize_config should have failed");
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, "InvalidAdmin");
+    }
+  });
+
+  it("Take is rejected until the config exists", async () => {
+    const early = new BN(randomBytes(8));
+    const escrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        early.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const earlyAccounts = {
+      ...accounts,
+      escrow,
+      vault: getAssociatedTokenAddressSync(
+        mintA.publicKey,
+        escrow,
+        true,
+        tokenProgram
+      ),
+    };
+
+    await program.methods
+      .make(early, new BN(1e6), new BN(1e6), new BN(0), new BN(0), null, null)
+      .accounts({ ...earlyAccounts })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .take(new BN(1e6))
+        .accounts({ ...earlyAccounts })
+        .signers([taker])
+        .rpc();
+      assert.fail("take should have failed");
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, "AccountNotInitialized");
+    }
+
+    await program.methods
+      .refund()
+      .accounts({ ...earlyAccounts })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+  });
+
+  it("Initialize config", async () => {
+    await program.methods
+      .initializeConfig(100, feeRecipient.publicKey, 2000)
+      .accounts({ admin: provider.publicKey, programData })
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        referrerAtaB,
+        referrer.publicKey,
+        mintB.publicKey,
+        tokenProgram
+      )
+    );
+    await provider.sendAndConfirm(tx);
+  });
+
+  it("Only the admin can update the config", async () => {
+    try {
+      await program.methods
+        .updateConfig(taker.publicKey, 0, taker.publicKey, 0)
+        .accounts({ admin: taker.publicKey })
+        .signers([taker])
+        .rpc();
+      assert.fail("update_config should have failed");
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, "InvalidAdmin");
+    }
+  });
+
+  it("Make rejects invalid offers", async () => {
+    const cases = [
+      {
+        deposit: 0,
+        receive: 1e6,
+        mintB: mintB.publicKey,
+        code: "InvalidDeposit",
+      },
+      {
+        deposit: 1e6,
+        receive: 0,
+        mintB: mintB.publicKey,
+        code: "InvalidReceive",
+      },
+      {
+        deposit: 1e6,
+        receive: 1e6,
+        mintB: mintA.publicKey,
+        code: "IdenticalMints",
+      },
+    ];
+
+    for (const c of cases) {
+      try {
+        await program.methods
+          .make(
+            seed,
+            new BN(c.deposit),
+            new BN(c.receive),
+            new BN(0),
+            new BN(0),
+            null,
+            null
+          )
+          .accounts({ ...accounts, mintB: c.mintB })
+          .signers([maker])
+          .rpc();
+        assert.fail("make should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, c.code);
+      }
+    }
+  });
+
   it("Make", async () => {
     await program.methods
-      .make(seed, new BN(1e6), new BN(1e6))
+      .make(
+        seed,
+        new BN(1e6),
+        new BN(1e6),
+        new BN(0),
+        new BN(0),
+        null,
+        null
+      )
       .accounts({ ...accounts })
       .signers([maker])
       .rpc()
@@ -170,11 +346,24 @@ describe("anchor-escrow", () => {
       .then(log);
   });
 
+  it("Take rejects the taker as their own referrer", async () => {
+    try {
+      await program.methods
+        .take(new BN(1e6))
+        .accounts({ ...accounts, referrerAtaB: takerAtaB })
+        .signers([taker])
+        .rpc();
+      assert.fail("take should have failed");
+    } catch (e) {
+      assert.equal(e.error?.errorCode?.code, "InvalidReferrer");
+    }
+  });
+
   it("Take", async () => {
     try {
       await program.methods
-        .take()
-        .accounts({ ...accounts })
+        .take(new BN(1e6))
+        .accounts({ ...accounts, referrerAtaB })
         .signers([taker])
         .rpc()
         .then(confirm)
@@ -183,5 +372,988 @@ describe("anchor-escrow", () => {
       console.log(e);
       throw e;
     }
+
+    const balance = async (account: PublicKey) =>
+      (await connection.getTokenAccountBalance(account)).value.amount;
+    assert.equal(await balance(makerAtaB), "990000");
+    assert.equal(await balance(feeRecipientAtaB), "8000");
+    assert.equal(await balance(referrerAtaB), "2000");
+  });
+
+  describe("partial fills", () => {
+    const partialSeed = new BN(randomBytes(8));
+
+    const partialEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        partialSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    const partialVault = getAssociatedTokenAddressSync(
+      mintA.publicKey,
+      partialEscrow,
+      true,
+      tokenProgram
+    );
+
+    const partialAccounts = {
+      ...accounts,
+      escrow: partialEscrow,
+      vault: partialVault,
+    };
+
+    it("Make", async () => {
+      await program.methods
+        .make(
+          partialSeed,
+          new BN(1e6),
+          new BN(2e6),
+          new BN(0),
+          new BN(0),
+          null,
+          null
+        )
+        .accounts({ ...partialAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+    });
+
+    it("Take part of the offer", async () => {
+      await program.methods
+        .takePartial(new BN(5e5), new BN(25e4))
+        .accounts({ ...partialAccounts })
+        .signers([taker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const state = await program.account.escrow.fetch(partialEscrow);
+      assert.equal(state.deposit.toNumber(), 75e4);
+      assert.equal(state.receive.toNumber(), 15e5);
+
+      const vaultBalance = await connection.getTokenAccountBalance(partialVault);
+      assert.equal(vaultBalance.value.amount, "750000");
+    });
+
+    it("Filling the rest closes the escrow", async () => {
+      await program.methods
+        .takePartial(new BN(15e5), new BN(75e4))
+        .accounts({ ...partialAccounts })
+        .signers([taker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      assert.isNull(await connection.getAccountInfo(partialEscrow));
+      assert.isNull(await connection.getAccountInfo(partialVault));
+    });
+  });
+
+  describe("expiry", () => {
+    const expiringSeed = new BN(randomBytes(8));
+    const cleanupReward = 1_000_000;
+
+    const expiringEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        expiringSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    const expiringVault = getAssociatedTokenAddressSync(
+      mintA.publicKey,
+      expiringEscrow,
+      true,
+      tokenProgram
+    );
+
+    const expiringAccounts = {
+      ...accounts,
+      escrow: expiringEscrow,
+      vault: expiringVault,
+    };
+
+    const caller = Keypair.generate();
+
+    it("Make an offer that expires in two seconds", async () => {
+      const expiresAt = Math.floor(Date.now() / 1000) + 2;
+      await program.methods
+        .make(
+          expiringSeed,
+          new BN(1e6),
+          new BN(1e6),
+          new BN(expiresAt),
+          new BN(cleanupReward),
+          null,
+          null
+        )
+        .accounts({ ...expiringAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: provider.publicKey,
+            toPubkey: caller.publicKey,
+            lamports: LAMPORTS_PER_SOL,
+          })
+        )
+      );
+    });
+
+    it("Expire is rejected before the deadline", async () => {
+      try {
+        await program.methods
+          .expire()
+          .accounts({ ...expiringAccounts, caller: caller.publicKey })
+          .signers([caller])
+          .rpc();
+        assert.fail("expire should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "OfferNotExpired");
+      }
+    });
+
+    it("Take is rejected after the deadline", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      try {
+        await program.methods
+          .take(new BN(1e6))
+          .accounts({ ...expiringAccounts })
+          .signers([taker])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "OfferExpired");
+      }
+    });
+
+    it("Anyone can expire the offer and collect the reward", async () => {
+      const before = await connection.getBalance(caller.publicKey);
+
+      await program.methods
+        .expire()
+        .accounts({ ...expiringAccounts, caller: caller.publicKey })
+        .signers([caller])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const after = await connection.getBalance(caller.publicKey);
+      assert.isAbove(after, before);
+      assert.isNull(await connection.getAccountInfo(expiringEscrow));
+      assert.isNull(await connection.getAccountInfo(expiringVault));
+    });
+  });
+
+  describe("private offers", () => {
+    const privateSeed = new BN(randomBytes(8));
+
+    const privateEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        privateSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    const privateVault = getAssociatedTokenAddressSync(
+      mintA.publicKey,
+      privateEscrow,
+      true,
+      tokenProgram
+    );
+
+    const privateAccounts = {
+      ...accounts,
+      escrow: privateEscrow,
+      vault: privateVault,
+    };
+
+    const stranger = Keypair.generate();
+    const [strangerAtaA, strangerAtaB] = [mintA, mintB].map((m) =>
+      getAssociatedTokenAddressSync(
+        m.publicKey,
+        stranger.publicKey,
+        false,
+        tokenProgram
+      )
+    );
+
+    it("Make an offer reserved for the taker", async () => {
+      await program.methods
+        .make(
+          privateSeed,
+          new BN(1e6),
+          new BN(1e6),
+          new BN(0),
+          new BN(0),
+          taker.publicKey,
+          null
+        )
+        .accounts({ ...privateAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const state = await program.account.escrow.fetch(privateEscrow);
+      assert.ok(state.taker.equals(taker.publicKey));
+
+      // Give a third party enough mint_b to pay for the offer.
+      const tx = new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: stranger.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        }),
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          strangerAtaB,
+          stranger.publicKey,
+          mintB.publicKey,
+          tokenProgram
+        ),
+        createMintToInstruction(
+          mintB.publicKey,
+          strangerAtaB,
+          taker.publicKey,
+          1e6,
+          undefined,
+          tokenProgram
+        )
+      );
+      await provider.sendAndConfirm(tx, [taker]);
+    });
+
+    it("A third party can't take it", async () => {
+      try {
+        await program.methods
+          .take(new BN(1e6))
+          .accounts({
+            ...privateAccounts,
+            taker: stranger.publicKey,
+            takerAtaA: strangerAtaA,
+            takerAtaB: strangerAtaB,
+          })
+          .signers([stranger])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "InvalidTaker");
+      }
+    });
+
+    it("A third party can't take part of it either", async () => {
+      try {
+        await program.methods
+          .takePartial(new BN(5e5), new BN(25e4))
+          .accounts({
+            ...privateAccounts,
+            taker: stranger.publicKey,
+            takerAtaA: strangerAtaA,
+            takerAtaB: strangerAtaB,
+          })
+          .signers([stranger])
+          .rpc();
+        assert.fail("take_partial should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "InvalidTaker");
+      }
+    });
+
+    it("The designated taker can", async () => {
+      await program.methods
+        .take(new BN(1e6))
+        .accounts({ ...privateAccounts })
+        .signers([taker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      assert.isNull(await connection.getAccountInfo(privateEscrow));
+    });
+  });
+
+  describe("basket offers", () => {
+    const basketSeed = new BN(randomBytes(8));
+    const mintC = Keypair.generate();
+
+    const basket = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("basket"),
+        maker.publicKey.toBuffer(),
+        basketSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    const ata = (mint: PublicKey, owner: PublicKey) =>
+      getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+
+    const offered = [mintA.publicKey, mintC.publicKey];
+
+    const basketAccounts = {
+      maker: maker.publicKey,
+      taker: taker.publicKey,
+      basket,
+      tokenProgram,
+    };
+
+    it("Create a second offered mint", async () => {
+      const lamports = await getMinimumBalanceForRentExemptMint(connection);
+      const makerAtaC = ata(mintC.publicKey, maker.publicKey);
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mintC.publicKey,
+          lamports,
+          space: MINT_SIZE,
+          programId: tokenProgram,
+        }),
+        createInitializeMint2Instruction(
+          mintC.publicKey,
+          0,
+          maker.publicKey,
+          null,
+          tokenProgram
+        ),
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          makerAtaC,
+          maker.publicKey,
+          mintC.publicKey,
+          tokenProgram
+        ),
+        createMintToInstruction(
+          mintC.publicKey,
+          makerAtaC,
+          maker.publicKey,
+          1,
+          undefined,
+          tokenProgram
+        )
+      );
+      await provider.sendAndConfirm(tx, [mintC, maker]);
+    });
+
+    it("Make a basket", async () => {
+      await program.methods
+        .makeBasket(
+          basketSeed,
+          [
+            { mint: mintA.publicKey, amount: new BN(1e6) },
+            { mint: mintC.publicKey, amount: new BN(1) },
+          ],
+          [{ mint: mintB.publicKey, amount: new BN(2e6) }]
+        )
+        .accounts({ ...basketAccounts })
+        .remainingAccounts(
+          offered.flatMap((mint) => [
+            { pubkey: mint, isSigner: false, isWritable: false },
+            {
+              pubkey: ata(mint, maker.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+            { pubkey: ata(mint, basket), isSigner: false, isWritable: true },
+          ])
+        )
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const vaultBalance = await connection.getTokenAccountBalance(
+        ata(mintC.publicKey, basket)
+      );
+      assert.equal(vaultBalance.value.amount, "1");
+    });
+
+    it("Take the basket", async () => {
+      await program.methods
+        .takeBasket()
+        .accounts({ ...basketAccounts })
+        .remainingAccounts([
+          ...offered.flatMap((mint) => [
+            { pubkey: mint, isSigner: false, isWritable: false },
+            { pubkey: ata(mint, basket), isSigner: false, isWritable: true },
+            {
+              pubkey: ata(mint, taker.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+          ]),
+          { pubkey: mintB.publicKey, isSigner: false, isWritable: false },
+          { pubkey: takerAtaB, isSigner: false, isWritable: true },
+          { pubkey: makerAtaB, isSigner: false, isWritable: true },
+        ])
+        .signers([taker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const takerC = await connection.getTokenAccountBalance(
+        ata(mintC.publicKey, taker.publicKey)
+      );
+      assert.equal(takerC.value.amount, "1");
+      assert.isNull(await connection.getAccountInfo(basket));
+      for (const mint of offered) {
+        assert.isNull(await connection.getAccountInfo(ata(mint, basket)));
+      }
+    });
+  });
+
+  describe("updates", () => {
+    const updateSeed = new BN(randomBytes(8));
+
+    const updateEscrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        updateSeed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    const updateVault = getAssociatedTokenAddressSync(
+      mintA.publicKey,
+      updateEscrow,
+      true,
+      tokenProgram
+    );
+
+    const updateAccounts = {
+      ...accounts,
+      escrow: updateEscrow,
+      vault: updateVault,
+    };
+
+    it("Make", async () => {
+      await program.methods
+        .make(
+          updateSeed,
+          new BN(1e6),
+          new BN(1e6),
+          new BN(0),
+          new BN(0),
+          null,
+          null
+        )
+        .accounts({ ...updateAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+    });
+
+    it("Top up the deposit and change the price", async () => {
+      await program.methods
+        .updateEscrow(new BN(3e6), new BN(2e6), null)
+        .accounts({ ...updateAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const state = await program.account.escrow.fetch(updateEscrow);
+      assert.equal(state.receive.toNumber(), 3e6);
+      assert.equal(state.deposit.toNumber(), 2e6);
+
+      const vaultBalance = await connection.getTokenAccountBalance(updateVault);
+      assert.equal(vaultBalance.value.amount, "2000000");
+    });
+
+    it("A take bounded by the old price fails after a reprice", async () => {
+      try {
+        await program.methods
+          .take(new BN(1e6))
+          .accounts({ ...updateAccounts })
+          .signers([taker])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "SlippageExceeded");
+      }
+
+      try {
+        await program.methods
+          .takePartial(new BN(5e5), new BN(5e5))
+          .accounts({ ...updateAccounts })
+          .signers([taker])
+          .rpc();
+        assert.fail("take_partial should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "SlippageExceeded");
+      }
+    });
+
+    it("Withdraw part of the deposit", async () => {
+      await program.methods
+        .updateEscrow(null, new BN(5e5), null)
+        .accounts({ ...updateAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const state = await program.account.escrow.fetch(updateEscrow);
+      assert.equal(state.receive.toNumber(), 3e6);
+      assert.equal(state.deposit.toNumber(), 5e5);
+
+      const vaultBalance = await connection.getTokenAccountBalance(updateVault);
+      assert.equal(vaultBalance.value.amount, "500000");
+    });
+
+    it("Refund", async () => {
+      await program.methods
+        .refund()
+        .accounts({ ...updateAccounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+    });
+  });
+
+  describe("vesting", () => {
+    const beneficiary = taker;
+
+    const vestingFor = (seed: BN) => {
+      const vesting = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vesting"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+
+      return {
+        maker: maker.publicKey,
+        beneficiary: beneficiary.publicKey,
+        mint: mintA.publicKey,
+        makerAta: makerAtaA,
+        beneficiaryAta: takerAtaA,
+        vesting,
+        vault: getAssociatedTokenAddressSync(
+          mintA.publicKey,
+          vesting,
+          true,
+          tokenProgram
+        ),
+        tokenProgram,
+      };
+    };
+
+    const started = new BN(randomBytes(8));
+    const cliffed = new BN(randomBytes(8));
+    const ended = new BN(randomBytes(8));
+
+    it("Make a schedule that is halfway through", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .makeVesting(
+          started,
+          new BN(1e6),
+          new BN(now - 100),
+          new BN(now - 100),
+          new BN(now + 100),
+          true
+        )
+        .accounts({ ...vestingFor(started) })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+    });
+
+    it("Claim what has vested", async () => {
+      const accounts = vestingFor(started);
+      const before = await connection.getTokenAccountBalance(accounts.vault);
+
+      await program.methods
+        .claimVested()
+        .accounts({ ...accounts })
+        .signers([beneficiary])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const state = await program.account.vesting.fetch(accounts.vesting);
+      assert.isAbove(state.claimed.toNumber(), 0);
+      assert.isBelow(state.claimed.toNumber(), 1e6);
+
+      const after = await connection.getTokenAccountBalance(accounts.vault);
+      assert.equal(
+        Number(before.value.amount) - Number(after.value.amount),
+        state.claimed.toNumber()
+      );
+    });
+
+    it("Claiming past the end pays out the whole vault and closes it", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const accounts = vestingFor(ended);
+
+      await program.methods
+        .makeVesting(
+          ended,
+          new BN(1e6),
+          new BN(now - 200),
+          new BN(now - 200),
+          new BN(now - 100),
+          false
+        )
+        .accounts({ ...accounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      // Tokens sent to the vault on top of the schedule must not block the
+      // final claim.
+      const tx = new Transaction().add(
+        createTransferCheckedInstruction(
+          makerAtaA,
+          mintA.publicKey,
+          accounts.vault,
+          maker.publicKey,
+          1,
+          6,
+          undefined,
+          tokenProgram
+        )
+      );
+      await provider.sendAndConfirm(tx, [maker]);
+
+      const before = await connection.getTokenAccountBalance(takerAtaA);
+
+      await program.methods
+        .claimVested()
+        .accounts({ ...accounts })
+        .signers([beneficiary])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const after = await connection.getTokenAccountBalance(takerAtaA);
+      assert.equal(
+        Number(after.value.amount) - Number(before.value.amount),
+        1e6 + 1
+      );
+      assert.isNull(await connection.getAccountInfo(accounts.vesting));
+      assert.isNull(await connection.getAccountInfo(accounts.vault));
+    });
+
+    it("Cancel returns the unvested remainder", async () => {
+      const accounts = vestingFor(started);
+
+      await program.methods
+        .cancelVesting()
+        .accounts({ ...accounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      assert.isNull(await connection.getAccountInfo(accounts.vesting));
+      assert.isNull(await connection.getAccountInfo(accounts.vault));
+    });
+
+    it("Nothing can be claimed before the cliff", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const accounts = vestingFor(cliffed);
+
+      await program.methods
+        .makeVesting(
+          cliffed,
+          new BN(1e6),
+          new BN(now),
+          new BN(now + 1000),
+          new BN(now + 2000),
+          false
+        )
+        .accounts({ ...accounts })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      try {
+        await program.methods
+          .claimVested()
+          .accounts({ ...accounts })
+          .signers([beneficiary])
+          .rpc();
+        assert.fail("claim_vested should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "NothingToClaim");
+      }
+
+      try {
+        await program.methods
+          .cancelVesting()
+          .accounts({ ...accounts })
+          .signers([maker])
+          .rpc();
+        assert.fail("cancel_vesting should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "NotCancellable");
+      }
+    });
+  });
+  describe("pool-priced offers", () => {
+    // anchor_amm lives in its own workspace; Anchor.toml loads it into the
+    // test validator.
+    const amm = new Program<AnchorAmm>(ammIdl as AnchorAmm, provider);
+
+    // anchor_amm's test flash-loan receiver, used to take an offer while its
+    // pool is mid-loan.
+    const receiver = new Program<FlashReceiver>(
+      receiverIdl as FlashReceiver,
+      provider
+    );
+
+    // anchor_amm pools hold classic SPL tokens and want their mints sorted.
+    const [mintX, mintY] = Array.from({ length: 2 }, () =>
+      Keypair.generate()
+    ).sort((a, b) => a.publicKey.toBuffer().compare(b.publicKey.toBuffer()));
+
+    const ata = (mint: Keypair, owner: PublicKey) =>
+      getAssociatedTokenAddressSync(
+        mint.publicKey,
+        owner,
+        true,
+        TOKEN_PROGRAM_ID
+      );
+
+    const pool = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("config"),
+        mintX.publicKey.toBuffer(),
+        mintY.publicKey.toBuffer(),
+        new BN(30).toArrayLike(Buffer, "le", 2),
+      ],
+      amm.programId
+    )[0];
+
+    const pricedFor = (seed: BN) => {
+      const escrow = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("escrow"),
+          maker.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+
+      return {
+        maker: maker.publicKey,
+        taker: taker.publicKey,
+        mintA: mintX.publicKey,
+        mintB: mintY.publicKey,
+        makerAtaA: ata(mintX, maker.publicKey),
+        makerAtaB: ata(mintY, maker.publicKey),
+        takerAtaA: ata(mintX, taker.publicKey),
+        takerAtaB: ata(mintY, taker.publicKey),
+        escrow,
+        vault: ata(mintX, escrow),
+        feeRecipient: feeRecipient.publicKey,
+        feeRecipientAtaB: ata(mintY, feeRecipient.publicKey),
+        pool,
+        poolVaultA: ata(mintX, pool),
+        poolVaultB: ata(mintY, pool),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+    };
+
+    // Offers priced at the pool plus a 1% premium, allowed to stray 5% from
+    // the maker's reference amount.
+    const price = { pool, premiumBps: 100, maxDeviationBps: 500 };
+    const [inside, outside, midLoan] = Array.from(
+      { length: 3 },
+      () => new BN(randomBytes(8))
+    );
+
+    const makePriced = (seed: BN, receive: number) =>
+      program.methods
+        .make(seed, new BN(1e6), new BN(receive), new BN(0), new BN(0), null, price)
+        .accounts({ ...pricedFor(seed) })
+        .signers([maker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+    it("Create a pool pricing X at 2 Y", async () => {
+      const lamports = await getMinimumBalanceForRentExemptMint(connection);
+      const tx = new Transaction();
+      tx.instructions = [
+        ...[mintX, mintY].flatMap((mint) => [
+          SystemProgram.createAccount({
+            fromPubkey: provider.publicKey,
+            newAccountPubkey: mint.publicKey,
+            lamports,
+            space: MINT_SIZE,
+            programId: TOKEN_PROGRAM_ID,
+          }),
+          createInitializeMint2Instruction(
+            mint.publicKey,
+            6,
+            provider.publicKey,
+            null,
+            TOKEN_PROGRAM_ID
+          ),
+        ]),
+        ...[
+          { mint: mintX, owner: maker.publicKey },
+          { mint: mintY, owner: taker.publicKey },
+          { mint: mintX, owner: provider.publicKey },
+          { mint: mintY, owner: provider.publicKey },
+        ].flatMap(({ mint, owner }) => [
+          createAssociatedTokenAccountIdempotentInstruction(
+            provider.publicKey,
+            ata(mint, owner),
+            owner,
+            mint.publicKey,
+            TOKEN_PROGRAM_ID
+          ),
+          createMintToInstruction(
+            mint.publicKey,
+            ata(mint, owner),
+            provider.publicKey,
+            1e10,
+            undefined,
+            TOKEN_PROGRAM_ID
+          ),
+        ]),
+      ];
+      await provider.sendAndConfirm(tx, [mintX, mintY]);
+
+      await amm.methods
+        .initialize(30, null, { constantProduct: {} }, new BN(0), false)
+        .accountsPartial({
+          initializer: provider.publicKey,
+          mintX: mintX.publicKey,
+          mintY: mintY.publicKey,
+          config: pool,
+        })
+        .rpc();
+
+      await amm.methods
+        .deposit(new BN(1), new BN(1e9), new BN(2e9))
+        .accountsPartial({
+          user: provider.publicKey,
+          mintX: mintX.publicKey,
+          mintY: mintY.publicKey,
+          config: pool,
+          allowListEntry: null,
+        })
+        .rpc();
+    });
+
+    it("Take at the pool price when it is close to the reference", async () => {
+      await makePriced(inside, 2e6);
+      const accounts = pricedFor(inside);
+
+      // 1e6 X at 2 Y each, plus the 1% premium.
+      const owed = 2_020_000;
+
+      try {
+        await program.methods
+          .take(new BN(owed - 1))
+          .accounts({ ...accounts })
+          .signers([taker])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "SlippageExceeded");
+      }
+
+      const before = await connection.getTokenAccountBalance(accounts.takerAtaB);
+
+      await program.methods
+        .take(new BN(owed))
+        .accounts({ ...accounts })
+        .signers([taker])
+        .rpc()
+        .then(confirm)
+        .then(log);
+
+      const after = await connection.getTokenAccountBalance(accounts.takerAtaB);
+      assert.equal(
+        Number(before.value.amount) - Number(after.value.amount),
+        owed
+      );
+      assert.isNull(await connection.getAccountInfo(accounts.escrow));
+    });
+
+    it("Reject the pool price when it strays past the max deviation", async () => {
+      // The pool says ~2.02e6 but the maker expected 1e6.
+      await makePriced(outside, 1e6);
+
+      try {
+        await program.methods
+          .take(new BN(1e7))
+          .accounts({ ...pricedFor(outside) })
+          .signers([taker])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "PriceDeviation");
+      }
+    });
+
+    it("Reject the pool price while the pool is mid-flash-loan", async () => {
+      await makePriced(midLoan, 2e6);
+
+      const take = await program.methods
+        .take(new BN(1e7))
+        .accounts({ ...pricedFor(midLoan) })
+        .instruction();
+
+      // The taker borrows Y from the pool, and the receiver tries the take
+      // before repaying.
+      const repay = await receiver.methods
+        .repay(new BN(1e6 + 3_000), take.data)
+        .accounts({
+          borrower: taker.publicKey,
+          borrowerAta: ata(mintY, taker.publicKey),
+          vault: ata(mintY, pool),
+        })
+        .remainingAccounts([
+          { pubkey: program.programId, isSigner: false, isWritable: false },
+          ...take.keys,
+        ])
+        .instruction();
+
+      try {
+        await amm.methods
+          .flashLoan(new BN(1e6), repay.data)
+          .accountsPartial({
+            borrower: taker.publicKey,
+            mint: mintY.publicKey,
+            config: pool,
+            allowListEntry: null,
+            receiver: receiver.programId,
+          })
+          .remainingAccounts(repay.keys)
+          .signers([taker])
+          .rpc();
+        assert.fail("take should have failed");
+      } catch (e) {
+        assert.equal(e.error?.errorCode?.code, "PoolUnavailable");
+      }
+    });
   });
-});
\ No newline at end of file
+});